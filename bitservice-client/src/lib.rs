@@ -4,6 +4,7 @@ use ark_groth16::VerifyingKey;
 use base64::{Engine as _, engine::general_purpose::STANDARD};
 use bitservice_types::{
     ban::{BanRequest, BanResponse, PeerBanRequest},
+    mmr,
     read::{PeerReadRequest, ReadRequest, ReadResponse},
     unban::{PeerUnbanRequest, UnbanRequest, UnbanResponse},
 };
@@ -221,6 +222,23 @@ impl Client {
             ]
         )?);
 
+        // verify each peer's root didn't replace history rather than extend it
+        assert!(mmr::verify_consistency(
+            res0.old_root,
+            res0.new_root,
+            &res0.consistency_proof
+        ));
+        assert!(mmr::verify_consistency(
+            res1.old_root,
+            res1.new_root,
+            &res1.consistency_proof
+        ));
+        assert!(mmr::verify_consistency(
+            res2.old_root,
+            res2.new_root,
+            &res2.consistency_proof
+        ));
+
         Ok(())
     }
 
@@ -311,6 +329,23 @@ impl Client {
             ]
         )?);
 
+        // verify each peer's root didn't replace history rather than extend it
+        assert!(mmr::verify_consistency(
+            res0.old_root,
+            res0.new_root,
+            &res0.consistency_proof
+        ));
+        assert!(mmr::verify_consistency(
+            res1.old_root,
+            res1.new_root,
+            &res1.consistency_proof
+        ));
+        assert!(mmr::verify_consistency(
+            res2.old_root,
+            res2.new_root,
+            &res2.consistency_proof
+        ));
+
         Ok(())
     }
 }
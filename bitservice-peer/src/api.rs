@@ -2,8 +2,11 @@
 //!
 //! This module defines all HTTP endpoints exposed by the bitservice peer and organizes them into submodules:
 //!
+//! - [`codec`] – Content-negotiated request/response bodies used by [`v1`]'s handlers.
 //! - [`errors`] – Defines API error types and conversions from internal service errors.
 //! - [`health`] – Provides health endpoint (`/health`).
+//! - [`peer_channel`] – Middleware enforcing the authenticated orchestrator channel on `v1`.
+//! - [`rate_limit`] – Middleware enforcing per-client-IP rate limiting on `v1`.
 //! - [`v1`] – Version 1 of the main bitservice server endpoints, including `/read` and `/write`.
 
 use axum::Router;
@@ -14,8 +17,11 @@ use crate::AppState;
 #[cfg(test)]
 use axum_test::TestServer;
 
+pub(crate) mod codec;
 pub(crate) mod errors;
 pub(crate) mod health;
+pub(crate) mod peer_channel;
+pub(crate) mod rate_limit;
 pub(crate) mod v1;
 
 /// Builds the main API router for the bitservice peer.
@@ -30,7 +36,7 @@ pub(crate) mod v1;
 /// instances needed to handle requests.
 pub(crate) fn new_app(app_state: AppState) -> Router {
     Router::new()
-        .nest("/api/v1", v1::build())
+        .nest("/api/v1", v1::build(app_state.clone()))
         .merge(health::routes())
         .layer(TraceLayer::new_for_http())
         .with_state(app_state)
@@ -0,0 +1,68 @@
+//! Content-negotiated request/response bodies.
+//!
+//! Wraps [`bitservice_types::codec`] so v1 handlers can accept and return any of the
+//! registered wire formats based on the request's `Content-Type`/`Accept` headers, instead of
+//! being hard-wired to JSON.
+
+use axum::{
+    body::Bytes,
+    extract::{FromRequest, Request},
+    http::header,
+    response::{IntoResponse, Response},
+};
+use bitservice_types::codec::{decode_for_content_type, encode_for_accept};
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::api::errors::ApiErrors;
+
+/// A request/response body that is decoded/encoded according to content negotiation rather
+/// than a fixed format.
+///
+/// On extraction, the codec is chosen from the request's `Content-Type` header. On response,
+/// the codec is chosen from the `Accept` value a handler passes to
+/// [`Encoded::into_response_for`]; [`IntoResponse`] alone always encodes JSON. Both directions
+/// fall back to JSON.
+pub(crate) struct Encoded<T>(pub(crate) T);
+
+impl<S, T> FromRequest<S> for Encoded<T>
+where
+    S: Send + Sync,
+    T: DeserializeOwned,
+{
+    type Rejection = ApiErrors;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let content_type = req
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_owned);
+        let bytes = Bytes::from_request(req, state)
+            .await
+            .map_err(|err| eyre::eyre!("while reading request body: {err}"))?;
+        let value = decode_for_content_type(content_type.as_deref(), &bytes)
+            .map_err(|err| eyre::eyre!("invalid request body: {err}"))?;
+        Ok(Self(value))
+    }
+}
+
+impl<T: Serialize> IntoResponse for Encoded<T> {
+    fn into_response(self) -> Response {
+        // Without access to the originating request we can't read its `Accept` header here;
+        // handlers that want a non-JSON response use [`Encoded::into_response_for`] instead.
+        Self::into_response_for(self, None)
+    }
+}
+
+impl<T: Serialize> Encoded<T> {
+    /// Encodes the response body using the codec negotiated from `accept` (typically the
+    /// incoming request's `Accept` header), falling back to JSON.
+    pub(crate) fn into_response_for(self, accept: Option<&str>) -> Response {
+        match encode_for_accept(accept, &self.0) {
+            Ok((body, content_type)) => {
+                ([(header::CONTENT_TYPE, content_type)], body).into_response()
+            }
+            Err(err) => ApiErrors::from(err).into_response(),
+        }
+    }
+}
@@ -0,0 +1,86 @@
+//! Enforces the authenticated peer channel (see [`bitservice_types::peer_channel`]) on the v1
+//! POST endpoints.
+//!
+//! When [`AppState::peer_channel`] is configured, a request body must be a
+//! [`SealedEnvelope`] that opens under it; anything else is rejected with `401` before it
+//! reaches a handler. The handler's response is sealed the same way on the way back out. When
+//! `peer_channel` is `None` (the default, unauthenticated, behavior) requests and responses pass
+//! through unchanged.
+
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use bitservice_types::peer_channel::SealedEnvelope;
+
+use crate::AppState;
+
+/// Upper bound on a sealed envelope's size, in either direction. This middleware has to buffer
+/// the whole body before it can even parse the envelope to authenticate it, so an unbounded cap
+/// would let an unauthenticated client force unbounded memory allocation with an oversized body -
+/// rate limiting alone doesn't help, since it caps requests/sec, not bytes/request. Generous
+/// enough for the largest real envelope (an MPC proof plus its commitment), nowhere near the size
+/// of a map dump, which never travels over this channel.
+const MAX_ENVELOPE_SIZE: usize = 16 * 1024 * 1024;
+
+pub(crate) async fn guard(State(state): State<AppState>, req: Request, next: Next) -> Response {
+    let Some(channel) = &state.peer_channel else {
+        return next.run(req).await;
+    };
+
+    let (mut parts, body) = req.into_parts();
+    let content_type = parts.headers.get(header::CONTENT_TYPE).cloned();
+    let body_bytes = match axum::body::to_bytes(body, MAX_ENVELOPE_SIZE).await {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            return (StatusCode::BAD_REQUEST, format!("invalid request body: {err}")).into_response();
+        }
+    };
+    let envelope: SealedEnvelope = match serde_json::from_slice(&body_bytes) {
+        Ok(envelope) => envelope,
+        Err(_) => {
+            return (
+                StatusCode::UNAUTHORIZED,
+                "request is not sealed for this peer's channel",
+            )
+                .into_response();
+        }
+    };
+    let plaintext = match envelope.open(channel) {
+        Ok(plaintext) => plaintext,
+        Err(_) => {
+            return (StatusCode::UNAUTHORIZED, "failed to authenticate sealed request").into_response();
+        }
+    };
+
+    if let Some(content_type) = content_type {
+        parts.headers.insert(header::CONTENT_TYPE, content_type);
+    }
+    let req = Request::from_parts(parts, Body::from(plaintext));
+
+    let response = next.run(req).await;
+    let (parts, body) = response.into_parts();
+    let response_bytes = match axum::body::to_bytes(body, MAX_ENVELOPE_SIZE).await {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            tracing::error!("failed to read response body for sealing: {err:?}");
+            return (StatusCode::INTERNAL_SERVER_ERROR, "internal error").into_response();
+        }
+    };
+    let sealed = SealedEnvelope::seal(channel, &response_bytes, &mut rand::thread_rng());
+    let sealed_bytes = match serde_json::to_vec(&sealed) {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            tracing::error!("failed to encode sealed response: {err:?}");
+            return (StatusCode::INTERNAL_SERVER_ERROR, "internal error").into_response();
+        }
+    };
+    let mut response = Response::from_parts(parts, Body::from(sealed_bytes));
+    response
+        .headers_mut()
+        .insert(header::CONTENT_TYPE, header::HeaderValue::from_static("application/json"));
+    response
+}
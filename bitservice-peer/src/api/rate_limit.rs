@@ -0,0 +1,32 @@
+//! Per-client-IP rate limiting on the v1 POST endpoints (see [`crate::rate_limiter`]). Applied
+//! alongside [`super::peer_channel::guard`] in `v1::build()` - unlike that guard, this one is
+//! unconditional, since abuse tracking doesn't depend on `peer_channel_kind`.
+
+use std::net::SocketAddr;
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::{AppState, client_identity::ClientIdentity};
+
+pub(crate) async fn guard(
+    State(state): State<AppState>,
+    ConnectInfo(peer_addr): ConnectInfo<SocketAddr>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let identity = ClientIdentity::extract(req.headers(), peer_addr, state.trust_forwarded_for);
+    if !state.rate_limiter.try_acquire(identity.ip).await {
+        tracing::warn!(
+            client_ip = %identity.ip,
+            user_agent = ?identity.user_agent,
+            "client exceeded rate limit"
+        );
+        return (StatusCode::TOO_MANY_REQUESTS, "rate limit exceeded").into_response();
+    }
+    next.run(req).await
+}
@@ -1,13 +1,21 @@
 //! Version 1 (v1) API Routes
 //!
 //! This module defines the v1 API routes for the bitservice peer.
-//! Currently, all endpoints are unauthenticated
+//! `read`/`ban`/`unban`/`prune` are additionally guarded by [`peer_channel::guard`] when this
+//! peer is configured with an orchestrator channel key (see
+//! [`PeerChannelKind`](crate::config::PeerChannelKind)); otherwise they remain unauthenticated.
+//! The same four routes are also rate-limited per client IP by [`rate_limit::guard`] (see
+//! [`crate::rate_limiter`]); `/ws` applies its own check in [`ws_handler`] instead, since it
+//! never carries a body for either middleware to run against.
 //!
 //! It also applies a restrictive CORS policy suitable for JSON-based POST requests.
 
+use std::net::SocketAddr;
+
 use axum::{
-    Json, Router,
-    extract::{Path, State, WebSocketUpgrade},
+    Router,
+    extract::{ConnectInfo, Path, State, WebSocketUpgrade},
+    http::StatusCode,
     response::Response,
     routing::{any, post},
 };
@@ -21,13 +29,30 @@ use http::HeaderMap;
 use tracing::instrument;
 use uuid::Uuid;
 
-use crate::{AppState, api::errors::ApiResult};
+use crate::{
+    AppState,
+    api::{codec::Encoded, errors::ApiResult, peer_channel, rate_limit},
+    client_identity::ClientIdentity,
+};
+
+/// Reads the `Accept` header so a response can be encoded with the codec the client asked for,
+/// falling back to JSON when absent or unrecognized (see [`bitservice_types::codec`]).
+fn accept(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(http::header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+}
 
 /// Build the v1 API router.
 ///
 /// This sets up:
 /// - a restrictive CORS layer allowing JSON POST requests and OPTIONS preflight and a wildcard origin.
-pub(crate) fn build() -> Router<AppState> {
+/// - [`rate_limit::guard`] on `read`/`ban`/`unban`/`prune`, run before a request is even checked
+///   against the peer channel, so an abusive client is turned away cheaply.
+/// - [`peer_channel::guard`] on the same routes, which only gates requests when
+///   `state.peer_channel` is configured (`/ws` never carries a body and stays unaffected by
+///   either).
+pub(crate) fn build(state: AppState) -> Router<AppState> {
     // TODO
     // // We setup a wildcard as we are a public API and everyone can access the service.
     // let cors = CorsLayer::new()
@@ -40,6 +65,11 @@ pub(crate) fn build() -> Router<AppState> {
         .route("/ban/{request_id}", post(ban))
         .route("/unban/{request_id}", post(unban))
         .route("/prune/{request_id}", post(prune))
+        .route_layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            peer_channel::guard,
+        ))
+        .route_layer(axum::middleware::from_fn_with_state(state, rate_limit::guard))
         .route("/ws", any(ws_handler))
 }
 
@@ -47,61 +77,77 @@ pub(crate) fn build() -> Router<AppState> {
 async fn read(
     State(state): State<AppState>,
     Path(request_id): Path<Uuid>,
-    Json(req): Json<PeerReadRequest>,
-) -> ApiResult<Json<PeerReadResponse>> {
+    headers: HeaderMap,
+    Encoded(req): Encoded<PeerReadRequest>,
+) -> ApiResult<Response> {
     tracing::debug!("received read request {request_id}");
     let res = state.ban_service.read(req, request_id).await?;
     tracing::debug!("handled read request {request_id}");
-    Ok(Json(res))
+    Ok(Encoded::<PeerReadResponse>(res).into_response_for(accept(&headers)))
 }
 
 #[instrument(level = "debug", skip_all, fields(request_id = %request_id))]
 async fn ban(
     State(state): State<AppState>,
     Path(request_id): Path<Uuid>,
-    Json(req): Json<PeerBanRequest>,
-) -> ApiResult<Json<PeerBanResponse>> {
+    headers: HeaderMap,
+    Encoded(req): Encoded<PeerBanRequest>,
+) -> ApiResult<Response> {
     tracing::debug!("received ban request {request_id}");
     let res = state.ban_service.ban(req, request_id).await?;
     tracing::debug!("handled ban request {request_id}");
-    Ok(Json(res))
+    Ok(Encoded::<PeerBanResponse>(res).into_response_for(accept(&headers)))
 }
 
 #[instrument(level = "debug", skip_all, fields(request_id = %request_id))]
 async fn unban(
     State(state): State<AppState>,
     Path(request_id): Path<Uuid>,
-    Json(req): Json<PeerUnbanRequest>,
-) -> ApiResult<Json<PeerUnbanResponse>> {
+    headers: HeaderMap,
+    Encoded(req): Encoded<PeerUnbanRequest>,
+) -> ApiResult<Response> {
     tracing::debug!("received unban request {request_id}");
     let res = state.ban_service.unban(req, request_id).await?;
     tracing::debug!("handled unban request {request_id}");
-    Ok(Json(res))
+    Ok(Encoded::<PeerUnbanResponse>(res).into_response_for(accept(&headers)))
 }
 
 #[instrument(level = "debug", skip_all, fields(request_id = %request_id))]
 async fn prune(
     State(state): State<AppState>,
     Path(request_id): Path<Uuid>,
-    Json(_req): Json<PeerPruneRequest>,
-) -> ApiResult<Json<PeerPruneResponse>> {
+    headers: HeaderMap,
+    Encoded(_req): Encoded<PeerPruneRequest>,
+) -> ApiResult<Response> {
     tracing::debug!("received prune request {request_id}");
     state.ban_service.prune(request_id).await?;
     tracing::debug!("handled prune request {request_id}");
-    Ok(Json(PeerPruneResponse {}))
+    Ok(Encoded(PeerPruneResponse {}).into_response_for(accept(&headers)))
 }
 
 /// The handler for the HTTP request (this gets called when the HTTP request lands at the start
 /// of websocket negotiation). After this completes, the actual switching from HTTP to
 /// websocket protocol will occur.
 /// This is the last point where we can extract TCP/IP metadata such as IP address of the client
-/// as well as things from HTTP headers such as user-agent of the browser etc.
+/// as well as things from HTTP headers such as user-agent of the browser etc. - which is exactly
+/// what [`ClientIdentity::extract`] does, so an abusive client can be turned away here, before a
+/// session is ever parked in `ws_sessions`.
 #[instrument(level = "debug", skip_all)]
 async fn ws_handler(
     headers: HeaderMap,
+    ConnectInfo(peer_addr): ConnectInfo<SocketAddr>,
     ws: WebSocketUpgrade,
     State(state): State<AppState>,
 ) -> axum::response::Result<Response> {
+    let identity = ClientIdentity::extract(&headers, peer_addr, state.trust_forwarded_for);
+    if !state.rate_limiter.try_acquire(identity.ip).await {
+        tracing::warn!(
+            client_ip = %identity.ip,
+            user_agent = ?identity.user_agent,
+            "dropping ws session: client exceeded rate limit"
+        );
+        return Err((StatusCode::TOO_MANY_REQUESTS, "rate limit exceeded").into());
+    }
     state
         .ban_service
         .ws_sessions
@@ -1,28 +1,244 @@
 use std::{
+    collections::HashMap,
     net::SocketAddr,
-    sync::Arc,
+    path::PathBuf,
+    sync::{Arc, Mutex},
     time::{Duration, Instant},
 };
 
 use base64::{Engine as _, engine::general_purpose::STANDARD};
 use bitservice_types::{
     ban::{PeerBanRequest, PeerBanResponse},
+    mmr::Mmr,
     read::{PeerReadRequest, PeerReadResponse},
     unban::{PeerUnbanRequest, PeerUnbanResponse},
 };
 use mpc_core::protocols::rep3::{Rep3State, conversion::A2BType, id::PartyID};
+use mpc_net::{ConnectionStats, Network};
 use oblivious_linear_scan_map::{
     Groth16Material, LinearScanObliviousMap, ObliviousReadRequest, ObliviousUpdateRequest,
 };
 use serde::de::DeserializeOwned;
-use tcp_mpc_net::{TcpNetwork, TcpSessions};
-use tokio::sync::RwLock;
+use tcp_mpc_net::{
+    ReconnectConfig as TcpReconnectConfig, RedialStrategy, TcpNetwork, TcpSessions,
+    TcpSessionsConfig,
+    auth::{AuthenticatedTcpNetwork, PeerIdentity as TcpPeerIdentity},
+};
+use tokio::sync::{Notify, RwLock, oneshot, watch};
 use tokio_util::sync::CancellationToken;
 use tracing::instrument;
 use uuid::Uuid;
-use ws_mpc_net::{WebSocketNetwork, WsSessions};
+use ws_mpc_net::{
+    ReconnectConfig, WebSocketNetwork, WsSessions, WsSessionsConfig,
+    auth::{AuthenticatedWebSocketNetwork, PeerIdentity},
+};
+
+use crate::{
+    config::TransportKind,
+    crypto_device::CryptoDevice,
+    proving_pool::ProvingPool,
+    repository::DbPool,
+};
+
+/// The two transports [`BanService`] can hand the oblivious map: trusting the websocket layer,
+/// or requiring a handshake and encrypting every frame (see `ws_mpc_net::auth`). Both sides of
+/// one MPC round always use the same variant, selected once via `transport_kind`.
+enum PeerNetwork {
+    Plain(WebSocketNetwork),
+    Authenticated(AuthenticatedWebSocketNetwork),
+}
+
+impl Network for PeerNetwork {
+    fn id(&self) -> usize {
+        match self {
+            Self::Plain(net) => net.id(),
+            Self::Authenticated(net) => net.id(),
+        }
+    }
+
+    fn send(&self, to: usize, data: &[u8]) -> eyre::Result<()> {
+        match self {
+            Self::Plain(net) => net.send(to, data),
+            Self::Authenticated(net) => net.send(to, data),
+        }
+    }
+
+    fn recv(&self, from: usize) -> eyre::Result<Vec<u8>> {
+        match self {
+            Self::Plain(net) => net.recv(from),
+            Self::Authenticated(net) => net.recv(from),
+        }
+    }
+
+    fn get_connection_stats(&self) -> ConnectionStats {
+        match self {
+            Self::Plain(net) => net.get_connection_stats(),
+            Self::Authenticated(net) => net.get_connection_stats(),
+        }
+    }
+}
+
+/// The TCP-transport counterpart of [`PeerNetwork`]: trusting the raw link (the previous, still
+/// default, behavior), or requiring the Secret-Handshake-style exchange in `tcp_mpc_net::auth`
+/// before any MPC traffic flows.
+enum PeerTcpNetwork {
+    Plain(TcpNetwork),
+    Authenticated(AuthenticatedTcpNetwork),
+}
+
+impl Network for PeerTcpNetwork {
+    fn id(&self) -> usize {
+        match self {
+            Self::Plain(net) => net.id(),
+            Self::Authenticated(net) => net.id(),
+        }
+    }
+
+    fn send(&self, to: usize, data: &[u8]) -> eyre::Result<()> {
+        match self {
+            Self::Plain(net) => net.send(to, data),
+            Self::Authenticated(net) => net.send(to, data),
+        }
+    }
+
+    fn recv(&self, from: usize) -> eyre::Result<Vec<u8>> {
+        match self {
+            Self::Plain(net) => net.recv(from),
+            Self::Authenticated(net) => net.recv(from),
+        }
+    }
+
+    fn get_connection_stats(&self) -> ConnectionStats {
+        match self {
+            Self::Plain(net) => net.get_connection_stats(),
+            Self::Authenticated(net) => net.get_connection_stats(),
+        }
+    }
+}
+
+/// A `ban` or `unban` write queued for [`run_batch_flush_loop`], carrying everything it needs
+/// to run the oblivious MPC op and reply to the caller.
+enum PendingWrite {
+    Ban {
+        req: ObliviousUpdateRequest,
+        net0: PeerNetwork,
+        net1: PeerNetwork,
+        reply: oneshot::Sender<eyre::Result<PeerBanResponse>>,
+    },
+    Unban {
+        req: ObliviousUpdateRequest,
+        net0: PeerNetwork,
+        net1: PeerNetwork,
+        reply: oneshot::Sender<eyre::Result<PeerUnbanResponse>>,
+    },
+}
+
+/// How long [`WriteDedup`] keeps a completed write's outcome around before a retry with the
+/// same `request_id` would no longer find it cached - mirrors `RateLimiter`'s idle-reap knobs.
+const WRITE_DEDUP_IDLE_TTL: Duration = Duration::from_secs(300);
+const WRITE_DEDUP_REAP_INTERVAL: Duration = Duration::from_secs(60);
 
-use crate::{crypto_device::CryptoDevice, repository::DbPool};
+/// One `request_id`'s outcome in a [`WriteDedup`] table: still running, or done and cached.
+enum WriteOutcome<T> {
+    /// A `watch` channel (rather than `oneshot`) so a retry that lands *after* the original
+    /// attempt already subscribed still observes the result: `watch::Receiver::borrow` always
+    /// returns the latest value, while a `oneshot` only delivers to whoever was already waiting.
+    InFlight(watch::Receiver<Option<Result<T, String>>>),
+    Done {
+        result: Result<T, String>,
+        completed_at: Instant,
+    },
+}
+
+/// De-duplicates `ban`/`unban` writes by `request_id`: the orchestrator's only way to retry a
+/// write whose response was lost to a timeout is to resend it with the same `request_id`, and
+/// resubmitting that write to the MPC path a second time would push a second entry onto
+/// `root_mmr` for what the orchestrator believes is one logical write (and race two
+/// `SessionGuard`s over the same `active_sessions` key). [`Self::dedup`] runs a write at most
+/// once per `request_id`, handing every other caller for that id the same outcome.
+#[derive(Clone)]
+struct WriteDedup<T> {
+    outcomes: Arc<Mutex<HashMap<Uuid, WriteOutcome<T>>>>,
+}
+
+impl<T: Clone + Send + 'static> WriteDedup<T> {
+    fn new() -> Self {
+        let dedup = Self {
+            outcomes: Arc::default(),
+        };
+        let dedup_clone = dedup.clone();
+        tokio::spawn(async move { dedup_clone.reap().await });
+        dedup
+    }
+
+    /// Periodically drops completed outcomes idle longer than [`WRITE_DEDUP_IDLE_TTL`], so the
+    /// table doesn't grow without bound as distinct `request_id`s come and go.
+    async fn reap(&self) {
+        let mut interval = tokio::time::interval(WRITE_DEDUP_REAP_INTERVAL);
+        loop {
+            interval.tick().await;
+            self.outcomes.lock().unwrap().retain(|_, outcome| match outcome {
+                WriteOutcome::InFlight(_) => true,
+                WriteOutcome::Done { completed_at, .. } => completed_at.elapsed() <= WRITE_DEDUP_IDLE_TTL,
+            });
+        }
+    }
+
+    /// Runs `op` unless `request_id` already has an in-flight or cached outcome, in which case
+    /// that outcome is returned (awaiting it first, if it's still in flight) instead of running
+    /// `op` again.
+    async fn dedup<F, Fut>(&self, request_id: Uuid, op: F) -> eyre::Result<T>
+    where
+        F: FnOnce() -> Fut,
+        Fut: std::future::Future<Output = eyre::Result<T>>,
+    {
+        let mut waiting_on = None;
+        let tx = {
+            let mut outcomes = self.outcomes.lock().unwrap();
+            match outcomes.get(&request_id) {
+                Some(WriteOutcome::InFlight(rx)) => {
+                    waiting_on = Some(rx.clone());
+                    None
+                }
+                Some(WriteOutcome::Done { result, .. }) => {
+                    return result.clone().map_err(|err| eyre::eyre!(err));
+                }
+                None => {
+                    let (tx, rx) = watch::channel::<Option<Result<T, String>>>(None);
+                    outcomes.insert(request_id, WriteOutcome::InFlight(rx));
+                    Some(tx)
+                }
+            }
+        };
+
+        let Some(tx) = tx else {
+            let mut rx = waiting_on.expect("either tx or waiting_on is set");
+            loop {
+                if let Some(result) = rx.borrow().clone() {
+                    return result.map_err(|err| eyre::eyre!(err));
+                }
+                rx.changed()
+                    .await
+                    .map_err(|_| eyre::eyre!("write for request_id {request_id} was dropped before completing"))?;
+            }
+        };
+
+        let result = op().await;
+        let cached: Result<T, String> = match &result {
+            Ok(value) => Ok(value.clone()),
+            Err(err) => Err(format!("{err:#}")),
+        };
+        self.outcomes.lock().unwrap().insert(
+            request_id,
+            WriteOutcome::Done {
+                result: cached.clone(),
+                completed_at: Instant::now(),
+            },
+        );
+        let _ = tx.send(Some(cached));
+        result
+    }
+}
 
 #[derive(Clone)]
 pub(crate) struct BanService {
@@ -34,9 +250,41 @@ pub(crate) struct BanService {
     #[allow(dead_code)]
     tcp_next_peer: SocketAddr,
     prev_peer_wait_timeout: Duration,
+    ws_idle_timeout: Duration,
+    ws_ping_interval: Duration,
+    peer_identity: Option<PeerIdentity>,
+    /// Mirrors `peer_identity` for the TCP transport; `None` means [`init_tcp_mpc_net`] hands
+    /// back a plain, unauthenticated [`TcpNetwork`].
+    tcp_peer_identity: Option<TcpPeerIdentity>,
     oblivious_map: Arc<RwLock<LinearScanObliviousMap>>,
+    /// Append-only history of the map's committed roots, one leaf per ban/unban, used to hand
+    /// clients a consistency proof binding `old_root` to `new_root`. Reseeded from `mmr_log` on
+    /// startup (see `DbPool::load_mmr`), so a restart's first consistency proof still chains
+    /// from whatever root a client's prior session last saw.
+    root_mmr: Arc<RwLock<Mmr>>,
     crypto_device: Arc<CryptoDevice>,
     db: Arc<DbPool>,
+    /// Runs the Groth16/rep3 proving for `read`/`ban`/`unban`/`prune` off the tokio reactor.
+    proving_pool: ProvingPool,
+    /// `ban`/`unban` writes queued for [`run_batch_flush_loop`] to coalesce into a single
+    /// `oblivious_map` write-lock acquisition, persisting each write via
+    /// `DbPool::append_operation` under that one lock.
+    pending_writes: Arc<Mutex<Vec<PendingWrite>>>,
+    /// Wakes [`run_batch_flush_loop`] when a write is queued or a new batch should start being
+    /// timed, mirroring `drain_notify`'s "register interest before checking" pattern.
+    batch_notify: Arc<Notify>,
+    /// Cancelled once shutdown begins; `read`/`ban`/`unban`/`prune` check this first and
+    /// reject new work instead of starting an MPC round that'll just get drained anyway.
+    shutdown_token: CancellationToken,
+    /// In-flight requests, keyed by `request_id`, so `shutdown` can wait for them to finish
+    /// (and, for writers, persist) before returning, and force-cancel whatever's left past
+    /// the drain timeout.
+    active_sessions: Arc<Mutex<HashMap<Uuid, CancellationToken>>>,
+    drain_notify: Arc<Notify>,
+    /// De-duplicates retried `ban` writes by `request_id`; see [`WriteDedup`].
+    ban_dedup: WriteDedup<PeerBanResponse>,
+    /// De-duplicates retried `unban` writes by `request_id`; see [`WriteDedup`].
+    unban_dedup: WriteDedup<PeerUnbanResponse>,
 }
 
 impl BanService {
@@ -47,30 +295,218 @@ impl BanService {
         next_peer: String,
         tcp_next_peer: SocketAddr,
         prev_peer_wait_timeout: Duration,
+        ws_idle_timeout: Duration,
+        ws_ping_interval: Duration,
+        ban_batch_max_size: usize,
+        ban_batch_max_delay: Duration,
+        transport_kind: TransportKind,
+        next_peer_public_key_path: Option<PathBuf>,
+        prev_peer_public_key_path: Option<PathBuf>,
+        tcp_identity_key_path: Option<PathBuf>,
+        next_peer_tcp_identity_key_path: Option<PathBuf>,
+        prev_peer_tcp_identity_key_path: Option<PathBuf>,
+        tcp_network_key_path: Option<PathBuf>,
         read_groth16: Groth16Material,
         write_groth16: Groth16Material,
         crypto_device: Arc<CryptoDevice>,
         db: DbPool,
     ) -> eyre::Result<Self> {
         let oblivious_map = db.load_or_init_map(read_groth16, write_groth16).await?;
+        let root_mmr = db.load_mmr().await?;
+        let peer_identity = match transport_kind {
+            TransportKind::Plain => None,
+            TransportKind::Authenticated => {
+                let next_peer_public_key_path = next_peer_public_key_path
+                    .ok_or_else(|| eyre::eyre!("next_peer_public_key_path is required for the authenticated transport"))?;
+                let prev_peer_public_key_path = prev_peer_public_key_path
+                    .ok_or_else(|| eyre::eyre!("prev_peer_public_key_path is required for the authenticated transport"))?;
+                let next_peer_public_key = crypto_box::PublicKey::from_slice(&std::fs::read(
+                    next_peer_public_key_path,
+                )?)?;
+                let prev_peer_public_key = crypto_box::PublicKey::from_slice(&std::fs::read(
+                    prev_peer_public_key_path,
+                )?)?;
+                Some(PeerIdentity {
+                    secret_key: crypto_device.secret_key().clone(),
+                    peer_public_keys: [
+                        (usize::from(party_id.next()), next_peer_public_key),
+                        (usize::from(party_id.prev()), prev_peer_public_key),
+                    ]
+                    .into_iter()
+                    .collect(),
+                })
+            }
+        };
+        let tcp_peer_identity = match transport_kind {
+            TransportKind::Plain => None,
+            TransportKind::Authenticated => {
+                let tcp_identity_key_path = tcp_identity_key_path
+                    .ok_or_else(|| eyre::eyre!("tcp_identity_key_path is required for the authenticated transport"))?;
+                let next_peer_tcp_identity_key_path = next_peer_tcp_identity_key_path
+                    .ok_or_else(|| eyre::eyre!("next_peer_tcp_identity_key_path is required for the authenticated transport"))?;
+                let prev_peer_tcp_identity_key_path = prev_peer_tcp_identity_key_path
+                    .ok_or_else(|| eyre::eyre!("prev_peer_tcp_identity_key_path is required for the authenticated transport"))?;
+                let tcp_network_key_path = tcp_network_key_path
+                    .ok_or_else(|| eyre::eyre!("tcp_network_key_path is required for the authenticated transport"))?;
+
+                let identity_key_bytes: [u8; 32] = std::fs::read(tcp_identity_key_path)?
+                    .try_into()
+                    .map_err(|_| eyre::eyre!("tcp identity key must be 32 bytes"))?;
+                let next_peer_identity_key_bytes: [u8; 32] =
+                    std::fs::read(next_peer_tcp_identity_key_path)?
+                        .try_into()
+                        .map_err(|_| eyre::eyre!("next peer tcp identity key must be 32 bytes"))?;
+                let prev_peer_identity_key_bytes: [u8; 32] =
+                    std::fs::read(prev_peer_tcp_identity_key_path)?
+                        .try_into()
+                        .map_err(|_| eyre::eyre!("prev peer tcp identity key must be 32 bytes"))?;
+                let network_key: [u8; 32] = std::fs::read(tcp_network_key_path)?
+                    .try_into()
+                    .map_err(|_| eyre::eyre!("tcp network key must be 32 bytes"))?;
+
+                Some(TcpPeerIdentity {
+                    identity_key: ed25519_dalek::SigningKey::from_bytes(&identity_key_bytes),
+                    network_key,
+                    peer_identity_keys: [
+                        (
+                            usize::from(party_id.next()),
+                            ed25519_dalek::VerifyingKey::from_bytes(&next_peer_identity_key_bytes)?,
+                        ),
+                        (
+                            usize::from(party_id.prev()),
+                            ed25519_dalek::VerifyingKey::from_bytes(&prev_peer_identity_key_bytes)?,
+                        ),
+                    ]
+                    .into_iter()
+                    .collect(),
+                })
+            }
+        };
+
+        let oblivious_map = Arc::new(RwLock::new(oblivious_map));
+        let root_mmr = Arc::new(RwLock::new(root_mmr));
+        let db = Arc::new(db);
+        let proving_pool = ProvingPool::new()?;
+        let pending_writes = Arc::new(Mutex::new(Vec::new()));
+        let batch_notify = Arc::new(Notify::new());
+
+        tokio::spawn(run_batch_flush_loop(
+            Arc::clone(&pending_writes),
+            Arc::clone(&batch_notify),
+            Arc::clone(&oblivious_map),
+            Arc::clone(&root_mmr),
+            Arc::clone(&db),
+            proving_pool.clone(),
+            ban_batch_max_size,
+            ban_batch_max_delay,
+        ));
+
         Ok(Self {
             party_id,
-            ws_sessions: WsSessions::default(),
-            tcp_sessions: TcpSessions::new(tcp_mpc_net_bind_addr).await?,
+            ws_sessions: WsSessions::new(WsSessionsConfig::default()),
+            tcp_sessions: TcpSessions::new(tcp_mpc_net_bind_addr, TcpSessionsConfig::default())
+                .await?,
             next_peer,
             tcp_next_peer,
             prev_peer_wait_timeout,
-            oblivious_map: Arc::new(RwLock::new(oblivious_map)),
+            ws_idle_timeout,
+            ws_ping_interval,
+            peer_identity,
+            tcp_peer_identity,
+            oblivious_map,
+            root_mmr,
             crypto_device,
-            db: Arc::new(db),
+            db,
+            proving_pool,
+            pending_writes,
+            batch_notify,
+            shutdown_token: CancellationToken::new(),
+            active_sessions: Arc::new(Mutex::new(HashMap::new())),
+            drain_notify: Arc::new(Notify::new()),
+            ban_dedup: WriteDedup::new(),
+            unban_dedup: WriteDedup::new(),
         })
     }
 
+    /// Rejects new work once shutdown has begun. Call at the top of every public request
+    /// handler, before any MPC round is started.
+    fn ensure_accepting_requests(&self) -> eyre::Result<()> {
+        if self.shutdown_token.is_cancelled() {
+            eyre::bail!("service is shutting down, rejecting new requests");
+        }
+        Ok(())
+    }
+
+    /// Tracks `request_id` as in-flight for the lifetime of the returned guard, so
+    /// `shutdown` knows to wait for it (and can force-cancel `token` if the drain times out).
+    fn register_session(&self, request_id: Uuid, token: CancellationToken) -> SessionGuard<'_> {
+        self.active_sessions.lock().unwrap().insert(request_id, token);
+        SessionGuard {
+            service: self,
+            request_id,
+        }
+    }
+
+    /// Stops accepting new `read`/`ban`/`unban`/`prune` requests, waits (up to
+    /// `drain_timeout`) for in-flight ones to finish - a `ban`/`unban` session stays tracked
+    /// until `run_batch_flush_loop` replies to it, which only happens after its
+    /// `self.db.append_operation` call has completed, so by the time a writer's session is no
+    /// longer tracked its commit has landed - and force-cancels whatever's still running past
+    /// the deadline.
+    pub(crate) async fn shutdown(&self, drain_timeout: Duration) {
+        tracing::info!(?drain_timeout, "shutting down ban service");
+        self.shutdown_token.cancel();
+
+        let deadline = tokio::time::sleep(drain_timeout);
+        tokio::pin!(deadline);
+
+        loop {
+            // Register interest in the next notification *before* checking, so a session
+            // that finishes between the check and the `select!` below isn't missed.
+            let notified = self.drain_notify.notified();
+
+            if self.active_sessions.lock().unwrap().is_empty() {
+                break;
+            }
+
+            tokio::select! {
+                _ = notified => {}
+                _ = &mut deadline => {
+                    let stuck: Vec<_> = self
+                        .active_sessions
+                        .lock()
+                        .unwrap()
+                        .values()
+                        .cloned()
+                        .collect();
+                    tracing::warn!(
+                        count = stuck.len(),
+                        "drain timeout exceeded, force-cancelling remaining sessions"
+                    );
+                    for token in stuck {
+                        token.cancel();
+                    }
+                    break;
+                }
+            }
+        }
+
+        // A barrier: `run_batch_flush_loop` holds this lock for the duration of a batch's
+        // `self.db.append_operation` calls, and by now every session that could have been
+        // waiting on such a batch has already been replied to (see above), so acquiring (and
+        // immediately dropping) it here is uncontended and confirms nothing is left to persist.
+        let _ = self.oblivious_map.write().await;
+
+        tracing::info!("ban service drained");
+    }
+
     pub(crate) async fn read(
         &self,
         req: PeerReadRequest,
         request_id: Uuid,
     ) -> eyre::Result<PeerReadResponse> {
+        self.ensure_accepting_requests()?;
+
         let key = decode_unseal_deser(&self.crypto_device, &req.key, "key")?;
         let r = decode_unseal_deser(&self.crypto_device, &req.r, "r")?;
         let req = ObliviousReadRequest {
@@ -79,6 +515,7 @@ impl BanService {
         };
 
         let cancellation_token = CancellationToken::new();
+        let _session_guard = self.register_session(request_id, cancellation_token.clone());
         let (net0, net1) = tokio::join!(
             self.init_ws_mpc_net(
                 Uuid::new_v5(&request_id, b"net0"),
@@ -92,13 +529,16 @@ impl BanService {
         let net0 = net0?;
         let net1 = net1?;
 
-        let oblivious_map = self.oblivious_map.read().await;
+        let oblivious_map = self.oblivious_map.clone().read_owned().await;
 
         let start = Instant::now();
-        let res = tokio::task::block_in_place(|| {
-            let mut rep3_state = Rep3State::new(&net0, A2BType::default())?;
-            oblivious_map.oblivious_read(req, &net0, &net1, &mut rep3_state)
-        })?;
+        let res = self
+            .proving_pool
+            .spawn(move || {
+                let mut rep3_state = Rep3State::new(&net0, A2BType::default())?;
+                oblivious_map.oblivious_read(req, &net0, &net1, &mut rep3_state)
+            })
+            .await?;
         tracing::debug!("read took {:?}", start.elapsed());
 
         cancellation_token.cancel();
@@ -111,11 +551,22 @@ impl BanService {
         })
     }
 
+    /// Applies a `ban` write, de-duplicated by `request_id` (see [`WriteDedup`]) so a retry that
+    /// reuses the same `request_id` - the orchestrator's only option once a prior attempt's
+    /// response has been lost to a timeout - gets that attempt's result instead of being
+    /// resubmitted to the MPC write path.
     pub(crate) async fn ban(
         &self,
         req: PeerBanRequest,
         request_id: Uuid,
     ) -> eyre::Result<PeerBanResponse> {
+        self.ensure_accepting_requests()?;
+        self.ban_dedup
+            .dedup(request_id, || self.do_ban(req, request_id))
+            .await
+    }
+
+    async fn do_ban(&self, req: PeerBanRequest, request_id: Uuid) -> eyre::Result<PeerBanResponse> {
         let key = decode_unseal_deser(&self.crypto_device, &req.key, "key")?;
         let value = decode_unseal_deser(&self.crypto_device, &req.value, "value")?;
         let r_key = decode_unseal_deser(&self.crypto_device, &req.r_key, "r_key")?;
@@ -128,6 +579,7 @@ impl BanService {
         };
 
         let cancellation_token = CancellationToken::new();
+        let _session_guard = self.register_session(request_id, cancellation_token.clone());
         let (net0, net1) = tokio::join!(
             self.init_ws_mpc_net(
                 Uuid::new_v5(&request_id, b"net0"),
@@ -141,34 +593,42 @@ impl BanService {
         let net0 = net0?;
         let net1 = net1?;
 
-        let mut oblivious_map = self.oblivious_map.write().await;
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.pending_writes.lock().unwrap().push(PendingWrite::Ban {
+            req,
+            net0,
+            net1,
+            reply: reply_tx,
+        });
+        self.batch_notify.notify_waiters();
 
         let start = Instant::now();
-        let res = tokio::task::block_in_place(|| {
-            let mut rep3_state = Rep3State::new(&net0, A2BType::default())?;
-            oblivious_map.oblivious_insert_or_update(req, &net0, &net1, &mut rep3_state)
-        })?;
+        let res = reply_rx
+            .await
+            .map_err(|_| eyre::eyre!("batch writer dropped without a reply"))??;
         tracing::debug!("ban took {:?}", start.elapsed());
 
         cancellation_token.cancel();
 
-        tracing::debug!("store map in db");
-        self.db.store_map(&oblivious_map).await?;
-
-        Ok(PeerBanResponse {
-            proof: res.proof.into(),
-            old_root: res.old_root,
-            new_root: res.new_root,
-            commitment_key: res.commitment_key,
-            commitment_value: res.commitment_value,
-        })
+        Ok(res)
     }
 
+    /// Applies an `unban` write, de-duplicated by `request_id` (see [`WriteDedup`]) so a retry
+    /// that reuses the same `request_id` - the orchestrator's only option once a prior attempt's
+    /// response has been lost to a timeout - gets that attempt's result instead of being
+    /// resubmitted to the MPC write path.
     pub(crate) async fn unban(
         &self,
         req: PeerUnbanRequest,
         request_id: Uuid,
     ) -> eyre::Result<PeerUnbanResponse> {
+        self.ensure_accepting_requests()?;
+        self.unban_dedup
+            .dedup(request_id, || self.do_unban(req, request_id))
+            .await
+    }
+
+    async fn do_unban(&self, req: PeerUnbanRequest, request_id: Uuid) -> eyre::Result<PeerUnbanResponse> {
         let key = decode_unseal_deser(&self.crypto_device, &req.key, "key")?;
         let value = decode_unseal_deser(&self.crypto_device, &req.value, "value")?;
         let r_key = decode_unseal_deser(&self.crypto_device, &req.r_key, "r_key")?;
@@ -181,6 +641,7 @@ impl BanService {
         };
 
         let cancellation_token = CancellationToken::new();
+        let _session_guard = self.register_session(request_id, cancellation_token.clone());
         let (net0, net1) = tokio::join!(
             self.init_ws_mpc_net(
                 Uuid::new_v5(&request_id, b"net0"),
@@ -194,45 +655,54 @@ impl BanService {
         let net0 = net0?;
         let net1 = net1?;
 
-        let mut oblivious_map = self.oblivious_map.write().await;
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.pending_writes
+            .lock()
+            .unwrap()
+            .push(PendingWrite::Unban {
+                req,
+                net0,
+                net1,
+                reply: reply_tx,
+            });
+        self.batch_notify.notify_waiters();
 
         let start = Instant::now();
-        let res = tokio::task::block_in_place(|| {
-            let mut rep3_state = Rep3State::new(&net0, A2BType::default())?;
-            oblivious_map.oblivious_update(req, &net0, &net1, &mut rep3_state)
-        })?;
+        let res = reply_rx
+            .await
+            .map_err(|_| eyre::eyre!("batch writer dropped without a reply"))??;
         tracing::debug!("unban took {:?}", start.elapsed());
 
         cancellation_token.cancel();
 
-        tracing::debug!("store map in db");
-        self.db.store_map(&oblivious_map).await?;
-
-        Ok(PeerUnbanResponse {
-            proof: res.proof.into(),
-            old_root: res.old_root,
-            new_root: res.new_root,
-            commitment_key: res.commitment_key,
-            commitment_value: res.commitment_value,
-        })
+        Ok(res)
     }
 
     pub(crate) async fn prune(&self, request_id: Uuid) -> eyre::Result<()> {
+        self.ensure_accepting_requests()?;
+
         let cancellation_token = CancellationToken::new();
+        let _session_guard = self.register_session(request_id, cancellation_token.clone());
         let net = self
             .init_ws_mpc_net(request_id, cancellation_token.clone())
             .await?;
 
-        let mut oblivious_map = self.oblivious_map.write().await;
+        let oblivious_map = self.oblivious_map.clone().write_owned().await;
 
         let start = Instant::now();
-        tokio::task::block_in_place(|| oblivious_map.prune(&net))?;
+        let oblivious_map = self
+            .proving_pool
+            .spawn(move || {
+                oblivious_map.prune(&net)?;
+                Ok(oblivious_map)
+            })
+            .await?;
         tracing::debug!("unban took {:?}", start.elapsed());
 
         cancellation_token.cancel();
 
-        tracing::debug!("store map in db");
-        self.db.store_map(&oblivious_map).await?;
+        tracing::debug!("store map snapshot in db");
+        self.db.force_snapshot(&oblivious_map).await?;
 
         Ok(())
     }
@@ -243,7 +713,7 @@ impl BanService {
         &self,
         session_id: Uuid,
         cancellation_token: CancellationToken,
-    ) -> eyre::Result<TcpNetwork> {
+    ) -> eyre::Result<PeerTcpNetwork> {
         tracing::debug!("connecting to next_peer: {}", self.tcp_next_peer);
 
         let next_stream = tcp_mpc_net::tcp_connect(self.tcp_next_peer, session_id).await?;
@@ -256,12 +726,33 @@ impl BanService {
         .await??;
 
         tracing::debug!("creating mpc network");
-        let net = TcpNetwork::new(
-            self.party_id,
-            next_stream,
-            prev_stream,
-            cancellation_token.clone(),
-        )?;
+        let net = match &self.tcp_peer_identity {
+            None => PeerTcpNetwork::Plain(TcpNetwork::new(
+                self.party_id,
+                next_stream,
+                RedialStrategy::Connect {
+                    addr: self.tcp_next_peer,
+                    session_id,
+                },
+                prev_stream,
+                RedialStrategy::Accept {
+                    sessions: self.tcp_sessions.clone(),
+                    session_id,
+                },
+                TcpReconnectConfig::default(),
+                cancellation_token.clone(),
+            )?),
+            Some(identity) => PeerTcpNetwork::Authenticated(
+                AuthenticatedTcpNetwork::new(
+                    self.party_id,
+                    next_stream,
+                    prev_stream,
+                    cancellation_token.clone(),
+                    identity.clone(),
+                )
+                .await?,
+            ),
+        };
         Ok(net)
     }
 
@@ -270,7 +761,7 @@ impl BanService {
         &self,
         session_id: Uuid,
         cancellation_token: CancellationToken,
-    ) -> eyre::Result<WebSocketNetwork> {
+    ) -> eyre::Result<PeerNetwork> {
         tracing::debug!("connecting to next_peer: {}", self.next_peer);
 
         let next_websocket = ws_mpc_net::ws_connect(&self.next_peer, session_id).await?;
@@ -283,16 +774,226 @@ impl BanService {
         .await??;
 
         tracing::debug!("creating mpc network");
-        let net = WebSocketNetwork::new(
-            self.party_id,
-            next_websocket,
-            prev_websocket,
-            cancellation_token.clone(),
-        )?;
+        let net = match &self.peer_identity {
+            None => PeerNetwork::Plain(WebSocketNetwork::new(
+                self.party_id,
+                session_id,
+                self.next_peer.clone(),
+                next_websocket,
+                self.ws_sessions.clone(),
+                prev_websocket,
+                cancellation_token.clone(),
+                ReconnectConfig::default(),
+                self.ws_idle_timeout,
+                self.ws_ping_interval,
+            )?),
+            Some(identity) => PeerNetwork::Authenticated(AuthenticatedWebSocketNetwork::new(
+                self.party_id,
+                session_id,
+                self.next_peer.clone(),
+                next_websocket,
+                self.ws_sessions.clone(),
+                prev_websocket,
+                cancellation_token.clone(),
+                ReconnectConfig::default(),
+                self.ws_idle_timeout,
+                self.ws_ping_interval,
+                identity.clone(),
+            )?),
+        };
         Ok(net)
     }
 }
 
+/// Coalesces queued `ban`/`unban` writes into batches, each taking a single `oblivious_map`
+/// write-lock acquisition, running the individual oblivious MPC ops on `proving_pool`
+/// sequentially under that one lock and snapshotting the map as each completes (see
+/// `DbPool::append_operation`).
+///
+/// Each write in a batch still runs its own oblivious scan; one map slot is never checked
+/// against several keys within a single MPC round, since `oblivious_linear_scan_map`'s circuit
+/// isn't vendored in this repo. A batch shares one lock acquisition, but every successful write
+/// in it is still persisted by its own `DbPool::append_operation` call.
+#[expect(clippy::too_many_arguments)]
+async fn run_batch_flush_loop(
+    pending_writes: Arc<Mutex<Vec<PendingWrite>>>,
+    batch_notify: Arc<Notify>,
+    oblivious_map: Arc<RwLock<LinearScanObliviousMap>>,
+    root_mmr: Arc<RwLock<Mmr>>,
+    db: Arc<DbPool>,
+    proving_pool: ProvingPool,
+    batch_max_size: usize,
+    batch_max_delay: Duration,
+) {
+    loop {
+        // Register interest before checking, so a write queued between the check and the
+        // `await` below isn't missed - same pattern as `shutdown`'s drain loop.
+        let notified = batch_notify.notified();
+        if pending_writes.lock().unwrap().is_empty() {
+            notified.await;
+        }
+
+        // Let more writers join the batch, up to `batch_max_delay`, unless `batch_max_size` is
+        // already hit.
+        let deadline = tokio::time::sleep(batch_max_delay);
+        tokio::pin!(deadline);
+        while pending_writes.lock().unwrap().len() < batch_max_size {
+            let notified = batch_notify.notified();
+            tokio::select! {
+                _ = notified => {}
+                _ = &mut deadline => break,
+            }
+        }
+
+        let batch = std::mem::take(&mut *pending_writes.lock().unwrap());
+        if batch.is_empty() {
+            continue;
+        }
+
+        let mut map_guard = Arc::clone(&oblivious_map).write_owned().await;
+        let mut ban_successes = Vec::new();
+        let mut unban_successes = Vec::new();
+
+        for write in batch {
+            match write {
+                PendingWrite::Ban {
+                    req,
+                    net0,
+                    net1,
+                    reply,
+                } => {
+                    let guard = map_guard;
+                    match proving_pool
+                        .spawn(move || {
+                            let mut rep3_state = Rep3State::new(&net0, A2BType::default())?;
+                            let res = guard
+                                .oblivious_insert_or_update(req, &net0, &net1, &mut rep3_state)?;
+                            Ok((res, guard))
+                        })
+                        .await
+                    {
+                        Ok((res, guard)) => {
+                            map_guard = guard;
+                            // Dump the map now, while it reflects exactly the ops applied so far
+                            // (including this one) and none after - `map_guard` keeps mutating
+                            // through the rest of the batch, so a dump taken in phase 2 would
+                            // snapshot the whole batch's final state under every op's version.
+                            let mut snapshot = Vec::new();
+                            match map_guard.dump(&mut snapshot, ark_serialize::Compress::No) {
+                                Ok(()) => ban_successes.push((reply, res, snapshot)),
+                                Err(err) => {
+                                    let _ = reply.send(Err(eyre::Error::from(err)));
+                                }
+                            }
+                        }
+                        Err(err) => {
+                            let _ = reply.send(Err(err));
+                            map_guard = Arc::clone(&oblivious_map).write_owned().await;
+                        }
+                    }
+                }
+                PendingWrite::Unban {
+                    req,
+                    net0,
+                    net1,
+                    reply,
+                } => {
+                    let guard = map_guard;
+                    match proving_pool
+                        .spawn(move || {
+                            let mut rep3_state = Rep3State::new(&net0, A2BType::default())?;
+                            let res = guard.oblivious_update(req, &net0, &net1, &mut rep3_state)?;
+                            Ok((res, guard))
+                        })
+                        .await
+                    {
+                        Ok((res, guard)) => {
+                            map_guard = guard;
+                            // See the matching comment in the `Ban` arm above.
+                            let mut snapshot = Vec::new();
+                            match map_guard.dump(&mut snapshot, ark_serialize::Compress::No) {
+                                Ok(()) => unban_successes.push((reply, res, snapshot)),
+                                Err(err) => {
+                                    let _ = reply.send(Err(eyre::Error::from(err)));
+                                }
+                            }
+                        }
+                        Err(err) => {
+                            let _ = reply.send(Err(err));
+                            map_guard = Arc::clone(&oblivious_map).write_owned().await;
+                        }
+                    }
+                }
+            }
+        }
+
+        if ban_successes.is_empty() && unban_successes.is_empty() {
+            continue;
+        }
+
+        tracing::debug!(
+            batch_size = ban_successes.len() + unban_successes.len(),
+            "persisting batch"
+        );
+
+        let mut root_mmr = root_mmr.write().await;
+        for (reply, res, snapshot) in ban_successes {
+            if let Err(err) = db.append_operation(snapshot, res.new_root).await {
+                let _ = reply.send(Err(eyre::eyre!("failed to persist ban: {err:#}")));
+                continue;
+            }
+            let m = root_mmr.len();
+            root_mmr.push(res.new_root);
+            let consistency_proof = root_mmr.consistency_proof(m);
+            let _ = reply.send(Ok(PeerBanResponse {
+                proof: res.proof.into(),
+                old_root: res.old_root,
+                new_root: res.new_root,
+                commitment_key: res.commitment_key,
+                commitment_value: res.commitment_value,
+                consistency_proof,
+            }));
+        }
+        for (reply, res, snapshot) in unban_successes {
+            if let Err(err) = db.append_operation(snapshot, res.new_root).await {
+                let _ = reply.send(Err(eyre::eyre!("failed to persist unban: {err:#}")));
+                continue;
+            }
+            let m = root_mmr.len();
+            root_mmr.push(res.new_root);
+            let consistency_proof = root_mmr.consistency_proof(m);
+            let _ = reply.send(Ok(PeerUnbanResponse {
+                proof: res.proof.into(),
+                old_root: res.old_root,
+                new_root: res.new_root,
+                commitment_key: res.commitment_key,
+                commitment_value: res.commitment_value,
+                consistency_proof,
+            }));
+        }
+        drop(root_mmr);
+        drop(map_guard);
+    }
+}
+
+/// Deregisters a request from `BanService::active_sessions` (and wakes `shutdown`'s drain
+/// loop) when the handler that created it returns, however it returns.
+struct SessionGuard<'a> {
+    service: &'a BanService,
+    request_id: Uuid,
+}
+
+impl Drop for SessionGuard<'_> {
+    fn drop(&mut self) {
+        self.service
+            .active_sessions
+            .lock()
+            .unwrap()
+            .remove(&self.request_id);
+        self.service.drain_notify.notify_waiters();
+    }
+}
+
 fn decode_unseal_deser<T: DeserializeOwned>(
     crypto_device: &CryptoDevice,
     base64: &str,
@@ -308,3 +1009,73 @@ fn decode_unseal_deser<T: DeserializeOwned>(
         .map_err(|_| eyre::eyre!("invalid {field} share bytes"))?;
     Ok(value)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn dedup_returns_cached_result_without_rerunning_op() {
+        let dedup = WriteDedup::<u32>::new();
+        let request_id = Uuid::new_v4();
+        let runs = AtomicUsize::new(0);
+
+        let first = dedup
+            .dedup(request_id, || async {
+                runs.fetch_add(1, Ordering::SeqCst);
+                Ok(42)
+            })
+            .await
+            .unwrap();
+        let retry = dedup
+            .dedup(request_id, || async {
+                runs.fetch_add(1, Ordering::SeqCst);
+                Ok(0)
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(first, 42);
+        assert_eq!(retry, 42);
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn dedup_awaits_an_in_flight_attempt_instead_of_starting_a_second_one() {
+        let dedup = WriteDedup::<u32>::new();
+        let request_id = Uuid::new_v4();
+        let runs = Arc::new(AtomicUsize::new(0));
+        let (release_tx, release_rx) = tokio::sync::oneshot::channel();
+
+        let runs_clone = Arc::clone(&runs);
+        let dedup_clone = dedup.clone();
+        let original = tokio::spawn(async move {
+            dedup_clone
+                .dedup(request_id, move || async move {
+                    runs_clone.fetch_add(1, Ordering::SeqCst);
+                    release_rx.await.ok();
+                    Ok(7)
+                })
+                .await
+        });
+
+        // Give the first attempt a chance to register itself as in-flight before the retry
+        // lands.
+        tokio::task::yield_now().await;
+
+        let runs_clone = Arc::clone(&runs);
+        let retry = dedup.dedup(request_id, move || async move {
+            runs_clone.fetch_add(1, Ordering::SeqCst);
+            Ok(0)
+        });
+        let retry = tokio::spawn(retry);
+
+        release_tx.send(()).unwrap();
+
+        assert_eq!(original.await.unwrap().unwrap(), 7);
+        assert_eq!(retry.await.unwrap().unwrap(), 7);
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+    }
+}
@@ -1,6 +1,8 @@
 use std::{path::PathBuf, process::ExitCode};
 
 use clap::Parser;
+use ed25519_dalek::SigningKey;
+use rand_core::OsRng;
 
 #[derive(Parser, Debug)]
 pub struct KeyGenConfig {
@@ -17,6 +19,31 @@ fn main() -> eyre::Result<ExitCode> {
         let pk = sk.public_key();
         std::fs::write(config.out.join(format!("peer{i}.sk")), sk.to_bytes())?;
         std::fs::write(config.out.join(format!("peer{i}.pk")), pk.to_bytes())?;
+
+        // Identity key for the authenticated TCP transport (`tcp_mpc_net::auth`).
+        let tcp_identity_key = SigningKey::generate(&mut OsRng);
+        std::fs::write(
+            config.out.join(format!("peer{i}.tcp-identity.sk")),
+            tcp_identity_key.to_bytes(),
+        )?;
+        std::fs::write(
+            config.out.join(format!("peer{i}.tcp-identity.pk")),
+            tcp_identity_key.verifying_key().to_bytes(),
+        )?;
     }
+
+    // Pre-shared secret all three parties need to even attempt the TCP handshake - see
+    // `tcp_mpc_net::auth::PeerIdentity::network_key`.
+    let network_key: [u8; 32] = rand::random();
+    std::fs::write(config.out.join("tcp-network.key"), network_key)?;
+
+    // The orchestrator's identity for the authenticated peer channel (`bitservice_server::peer_channel`).
+    // Each peer authenticates it against `orchestrator.pk`; the peer side of that channel reuses
+    // `peer{i}.sk`/`peer{i}.pk`, generated above.
+    let orchestrator_sk = crypto_box::SecretKey::generate(&mut rng);
+    let orchestrator_pk = orchestrator_sk.public_key();
+    std::fs::write(config.out.join("orchestrator.sk"), orchestrator_sk.to_bytes())?;
+    std::fs::write(config.out.join("orchestrator.pk"), orchestrator_pk.to_bytes())?;
+
     Ok(ExitCode::SUCCESS)
 }
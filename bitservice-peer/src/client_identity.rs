@@ -0,0 +1,44 @@
+//! Derives a client's IP address and user-agent from an inbound request, honoring an optional
+//! trusted reverse proxy - used by [`crate::rate_limiter`] and the `ws_handler` request log.
+
+use std::net::{IpAddr, SocketAddr};
+
+use http::HeaderMap;
+
+/// IP address and user-agent of the client that opened a connection, as best as can be
+/// determined from TCP/IP and HTTP headers.
+#[derive(Debug, Clone)]
+pub(crate) struct ClientIdentity {
+    pub(crate) ip: IpAddr,
+    pub(crate) user_agent: Option<String>,
+}
+
+impl ClientIdentity {
+    /// Extracts the client's identity from `headers` and the TCP peer address `peer_addr`.
+    ///
+    /// When `trust_forwarded_for` is set, the left-most address in `X-Forwarded-For` is trusted
+    /// as the real client IP - the service is assumed to sit behind a reverse proxy that
+    /// overwrites that header rather than appending to whatever a client sent - otherwise
+    /// `peer_addr` (the TCP connection's actual peer) is used.
+    pub(crate) fn extract(headers: &HeaderMap, peer_addr: SocketAddr, trust_forwarded_for: bool) -> Self {
+        let ip = trust_forwarded_for
+            .then(|| forwarded_for_ip(headers))
+            .flatten()
+            .unwrap_or_else(|| peer_addr.ip());
+        let user_agent = headers
+            .get(http::header::USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_owned);
+        Self { ip, user_agent }
+    }
+}
+
+/// Parses the left-most address out of an `X-Forwarded-For` header, e.g. `"203.0.113.1, 10.0.0.1"`
+/// yields `203.0.113.1`.
+fn forwarded_for_ip(headers: &HeaderMap) -> Option<IpAddr> {
+    headers
+        .get("x-forwarded-for")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(',').next())
+        .and_then(|value| value.trim().parse().ok())
+}
@@ -24,6 +24,53 @@ impl Environment {
     }
 }
 
+/// Where `CryptoDevice` loads the peer's long-term secret key from.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum SecretBackend {
+    /// Read the key off local disk via `secret_key_path`. Only intended for `dev` - `start`
+    /// asserts `Environment::assert_is_dev` before using it.
+    File,
+    /// Fetch the key from AWS Secrets Manager by `aws_secret_id` at startup, so it's never
+    /// written to a local filesystem.
+    AwsSecretsManager,
+}
+
+/// Which channel secures `read`/`ban`/`unban`/`prune` requests arriving from the orchestrator.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum PeerChannelKind {
+    /// Trust the HTTP/TLS layer for orchestrator authentication (the previous, and still
+    /// default, behavior).
+    Plain,
+    /// Require every request/response body to be sealed under a mutually-authenticated
+    /// `crypto_box` keyed by this peer's and the orchestrator's static key. See
+    /// `crate::api::peer_channel`.
+    Authenticated,
+}
+
+/// How this peer's v1 API is reached by the orchestrator.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum PeerTransportKind {
+    /// The orchestrator connects in to this peer's HTTP server (the previous, and still
+    /// default, behavior).
+    Forward,
+    /// This peer instead dials the orchestrator's relay endpoint and is served over that
+    /// persistent connection, so it needs no inbound port reachable by the orchestrator. See
+    /// `crate::relay_client`.
+    Reverse,
+}
+
+/// Which transport the peer uses to talk to `next_peer`/the prev peer.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum TransportKind {
+    /// Trust the websocket/TLS layer for peer authentication (the previous, and still default,
+    /// behavior).
+    Plain,
+    /// Require each side to prove possession of its static key via a handshake before any MPC
+    /// traffic flows, and encrypt every frame under the resulting shared key. See
+    /// `ws_mpc_net::auth`.
+    Authenticated,
+}
+
 /// The configuration for the bitservice peer.
 ///
 /// It can be configured via environment variables or command line arguments using `clap`.
@@ -62,6 +109,87 @@ pub struct BitservicePeerConfig {
     )]
     pub prev_peer_wait_timeout: Duration,
 
+    /// How long a ws-mpc-net link may go without receiving anything (not even a Pong) before
+    /// it is considered dead and reconnected
+    #[clap(
+        long,
+        env = "BITSERVICE_PEER_WS_IDLE_TIMEOUT",
+        default_value = "30s",
+        value_parser = humantime::parse_duration
+    )]
+    pub ws_idle_timeout: Duration,
+
+    /// How often a ws-mpc-net link sends a keepalive Ping while idle
+    #[clap(
+        long,
+        env = "BITSERVICE_PEER_WS_PING_INTERVAL",
+        default_value = "10s",
+        value_parser = humantime::parse_duration
+    )]
+    pub ws_ping_interval: Duration,
+
+    /// On SIGINT/SIGTERM, how long to wait for in-flight read/ban/unban/prune requests to
+    /// finish (and, for writes, persist) before giving up and force-cancelling them
+    #[clap(
+        long,
+        env = "BITSERVICE_PEER_SHUTDOWN_DRAIN_TIMEOUT",
+        default_value = "30s",
+        value_parser = humantime::parse_duration
+    )]
+    pub shutdown_drain_timeout: Duration,
+
+    /// Maximum number of `ban`/`unban` writes coalesced into a single oblivious batch - see
+    /// `BanService`'s batch writer
+    #[clap(long, env = "BITSERVICE_PEER_BAN_BATCH_MAX_SIZE", default_value = "16")]
+    pub ban_batch_max_size: usize,
+
+    /// Maximum time a `ban`/`unban` write waits for more writes to join its batch before the
+    /// batch writer flushes it on its own
+    #[clap(
+        long,
+        env = "BITSERVICE_PEER_BAN_BATCH_MAX_DELAY",
+        default_value = "20ms",
+        value_parser = humantime::parse_duration
+    )]
+    pub ban_batch_max_delay: Duration,
+
+    /// Which transport to use for peer MPC traffic
+    #[clap(
+        long,
+        env = "BITSERVICE_PEER_TRANSPORT_KIND",
+        default_value = "plain"
+    )]
+    pub transport_kind: TransportKind,
+
+    /// Public key of the next peer. Required when `transport_kind` is `authenticated`
+    #[clap(long, env = "BITSERVICE_PEER_NEXT_PEER_PUBLIC_KEY_PATH")]
+    pub next_peer_public_key_path: Option<PathBuf>,
+
+    /// Public key of the prev peer. Required when `transport_kind` is `authenticated`
+    #[clap(long, env = "BITSERVICE_PEER_PREV_PEER_PUBLIC_KEY_PATH")]
+    pub prev_peer_public_key_path: Option<PathBuf>,
+
+    /// This party's identity key for the authenticated TCP transport (`tcp_mpc_net::auth`).
+    /// Required when `transport_kind` is `authenticated`
+    #[clap(long, env = "BITSERVICE_PEER_TCP_IDENTITY_KEY_PATH")]
+    pub tcp_identity_key_path: Option<PathBuf>,
+
+    /// Expected TCP transport identity key of the next peer. Required when `transport_kind` is
+    /// `authenticated`
+    #[clap(long, env = "BITSERVICE_PEER_NEXT_PEER_TCP_IDENTITY_KEY_PATH")]
+    pub next_peer_tcp_identity_key_path: Option<PathBuf>,
+
+    /// Expected TCP transport identity key of the prev peer. Required when `transport_kind` is
+    /// `authenticated`
+    #[clap(long, env = "BITSERVICE_PEER_PREV_PEER_TCP_IDENTITY_KEY_PATH")]
+    pub prev_peer_tcp_identity_key_path: Option<PathBuf>,
+
+    /// Pre-shared secret gating the authenticated TCP transport's handshake before either side's
+    /// identity is revealed (see `tcp_mpc_net::auth`). Required when `transport_kind` is
+    /// `authenticated`
+    #[clap(long, env = "BITSERVICE_PEER_TCP_NETWORK_KEY_PATH")]
+    pub tcp_network_key_path: Option<PathBuf>,
+
     /// The path to the read proving key
     #[clap(
         long,
@@ -110,12 +238,82 @@ pub struct BitservicePeerConfig {
     )]
     pub oblivious_map_write_proof_schema_path: PathBuf,
 
-    // TODO probably move to AWS secrets manager
-    /// The path to the peer secret key
+    /// Which backend `CryptoDevice` loads the peer secret key from
+    #[clap(long, env = "BITSERVICE_PEER_SECRET_BACKEND", default_value = "file")]
+    pub secret_backend: SecretBackend,
+
+    /// The path to the peer secret key. Required when `secret_backend` is `file`
     #[clap(long, env = "BITSERVICE_PEER_SECRET_KEY_PATH")]
-    pub secret_key_path: PathBuf,
+    pub secret_key_path: Option<PathBuf>,
+
+    /// The AWS Secrets Manager secret id holding the peer secret key. Required when
+    /// `secret_backend` is `aws-secrets-manager`
+    #[clap(long, env = "BITSERVICE_PEER_AWS_SECRET_ID")]
+    pub aws_secret_id: Option<String>,
 
     /// The URL for the peer's DB
     #[clap(long, env = "BITSERVICE_PEER_DB_URL")]
     pub db_url: SecretString,
+
+    /// Which channel secures requests/responses arriving at the v1 API from the orchestrator
+    #[clap(
+        long,
+        env = "BITSERVICE_PEER_PEER_CHANNEL_KIND",
+        default_value = "plain"
+    )]
+    pub peer_channel_kind: PeerChannelKind,
+
+    /// Static public key of the orchestrator's peer channel. Required when `peer_channel_kind`
+    /// is `authenticated`
+    #[clap(long, env = "BITSERVICE_PEER_ORCHESTRATOR_PUBLIC_KEY_PATH")]
+    pub orchestrator_public_key_path: Option<PathBuf>,
+
+    /// Requests a client IP may make before the steady-state `rate_limit_per_sec` applies -
+    /// see `crate::rate_limiter`
+    #[clap(long, env = "BITSERVICE_PEER_RATE_LIMIT_BURST", default_value = "40")]
+    pub rate_limit_burst: u32,
+
+    /// Requests per second admitted per client IP once its burst is spent
+    #[clap(long, env = "BITSERVICE_PEER_RATE_LIMIT_PER_SEC", default_value = "20")]
+    pub rate_limit_per_sec: f64,
+
+    /// Trust the `X-Forwarded-For` header for a client's real IP instead of the TCP peer
+    /// address - only enable this behind a reverse proxy that overwrites rather than appends to
+    /// that header
+    #[clap(long, env = "BITSERVICE_PEER_TRUST_FORWARDED_FOR", default_value = "false")]
+    pub trust_forwarded_for: bool,
+
+    /// How this peer's v1 API is reached by the orchestrator
+    #[clap(
+        long,
+        env = "BITSERVICE_PEER_PEER_TRANSPORT_KIND",
+        default_value = "forward"
+    )]
+    pub peer_transport_kind: PeerTransportKind,
+
+    /// The orchestrator's relay endpoint to dial. Required when `peer_transport_kind` is
+    /// `reverse`
+    #[clap(long, env = "BITSERVICE_PEER_RELAY_URL")]
+    pub relay_url: Option<String>,
+
+    /// The id this peer presents when it dials the orchestrator's `/relay/{peer_id}` endpoint -
+    /// must match the `peer_id` configured for it in the orchestrator's
+    /// `rp_bitservice_peers_config`. Required when `peer_transport_kind` is `reverse`
+    #[clap(long, env = "BITSERVICE_PEER_RELAY_PEER_ID")]
+    pub relay_peer_id: Option<String>,
+
+    /// Shared secret this peer presents when dialing the orchestrator's `/relay/{peer_id}`
+    /// endpoint - must match the orchestrator's `relay_shared_secret`. Required when
+    /// `peer_transport_kind` is `reverse`
+    #[clap(long, env = "BITSERVICE_PEER_RELAY_SHARED_SECRET")]
+    pub relay_shared_secret: Option<SecretString>,
+
+    /// How long a relay request may go unanswered before this peer gives up on it
+    #[clap(
+        long,
+        env = "BITSERVICE_PEER_RELAY_REQUEST_TIMEOUT",
+        default_value = "60s",
+        value_parser = humantime::parse_duration
+    )]
+    pub relay_request_timeout: Duration,
 }
@@ -1,12 +1,14 @@
-use std::path::Path;
+use secrecy::ExposeSecret;
+
+use crate::secret_provider::SecretProviderKind;
 
 pub(crate) type Result<T> = std::result::Result<T, CryptoDeviceError>;
 
 #[derive(Debug, thiserror::Error)]
 pub(crate) enum CryptoDeviceError {
-    /// IO error
-    #[error(transparent)]
-    IoError(#[from] std::io::Error),
+    /// Failed to load the secret key from the configured `SecretProvider`
+    #[error("failed to load secret key: {0}")]
+    SecretProviderError(String),
     /// Invalid secret key bytes
     #[error(transparent)]
     InvalidSecretKey(#[from] std::array::TryFromSliceError),
@@ -20,13 +22,22 @@ pub struct CryptoDevice {
 }
 
 impl CryptoDevice {
-    pub(crate) fn new(secret_key_path: impl AsRef<Path>) -> Result<Self> {
-        let sk_bytes = std::fs::read(secret_key_path)?;
-        let sk = crypto_box::SecretKey::from_slice(&sk_bytes)?;
+    pub(crate) async fn new(secret_provider: &SecretProviderKind) -> Result<Self> {
+        let secret_key_bytes = secret_provider
+            .load_secret_key()
+            .await
+            .map_err(|err| CryptoDeviceError::SecretProviderError(format!("{err:#}")))?;
+        let sk = crypto_box::SecretKey::from_slice(secret_key_bytes.expose_secret())?;
         Ok(Self { sk })
     }
 
     pub(crate) fn unseal(&self, ciphertext: &[u8]) -> Result<Vec<u8>> {
         Ok(self.sk.unseal(ciphertext)?)
     }
+
+    /// This party's long-term secret key, used to authenticate itself to other peers (see
+    /// `ws_mpc_net::auth`).
+    pub(crate) fn secret_key(&self) -> &crypto_box::SecretKey {
+        &self.sk
+    }
 }
@@ -1,4 +1,4 @@
-use std::{fs::File, sync::Arc};
+use std::{fs::File, sync::Arc, time::Duration};
 
 use ark_groth16::ProvingKey;
 use ark_serialize::CanonicalDeserialize;
@@ -8,16 +8,25 @@ use oblivious_linear_scan_map::Groth16Material;
 use secrecy::ExposeSecret;
 
 use crate::{
-    ban_service::BanService, config::BitservicePeerConfig, crypto_device::CryptoDevice,
+    ban_service::BanService,
+    config::{BitservicePeerConfig, PeerChannelKind, PeerTransportKind, SecretBackend},
+    crypto_device::CryptoDevice,
+    rate_limiter::{RateLimiter, RateLimiterConfig},
     repository::DbPool,
+    secret_provider::{AwsSecretsManagerProvider, FileSecretProvider, SecretProviderKind},
 };
 
 pub(crate) mod api;
 pub(crate) mod ban_service;
+pub(crate) mod client_identity;
 pub mod config;
 pub(crate) mod crypto_device;
 pub mod metrics;
+pub(crate) mod proving_pool;
+pub(crate) mod rate_limiter;
+pub(crate) mod relay_client;
 pub(crate) mod repository;
+pub(crate) mod secret_provider;
 
 /// Main application state for the bitservice-server used for Axum.
 ///
@@ -26,6 +35,15 @@ pub(crate) mod repository;
 #[derive(Clone)]
 pub(crate) struct AppState {
     ban_service: BanService,
+    /// `Some` when `peer_channel_kind` is `authenticated`: the box every v1 POST request must
+    /// be sealed under, and every response is sealed back under. See `api::peer_channel` and,
+    /// for requests arriving over a relay connection instead, `relay_client::dispatch`.
+    peer_channel: Option<Arc<crypto_box::SalsaBox>>,
+    /// Per-client-IP token bucket backing `api::rate_limit::guard` and `api::v1::ws_handler`.
+    rate_limiter: RateLimiter,
+    /// Whether `X-Forwarded-For` is trusted to carry the real client IP. See
+    /// `client_identity::ClientIdentity::extract`.
+    trust_forwarded_for: bool,
 }
 
 pub async fn start(config: BitservicePeerConfig) -> eyre::Result<()> {
@@ -33,7 +51,38 @@ pub async fn start(config: BitservicePeerConfig) -> eyre::Result<()> {
 
     let db = DbPool::open(config.db_url.expose_secret()).await?;
 
-    let crypto_device = Arc::new(CryptoDevice::new(config.secret_key_path)?);
+    let secret_provider = match config.secret_backend {
+        SecretBackend::File => {
+            config.environment.assert_is_dev();
+            let secret_key_path = config
+                .secret_key_path
+                .ok_or_else(|| eyre::eyre!("secret_key_path is required for the file secret backend"))?;
+            SecretProviderKind::File(FileSecretProvider::new(secret_key_path))
+        }
+        SecretBackend::AwsSecretsManager => {
+            let aws_secret_id = config.aws_secret_id.ok_or_else(|| {
+                eyre::eyre!("aws_secret_id is required for the aws-secrets-manager secret backend")
+            })?;
+            SecretProviderKind::AwsSecretsManager(AwsSecretsManagerProvider::new(aws_secret_id).await?)
+        }
+    };
+    let crypto_device = Arc::new(CryptoDevice::new(&secret_provider).await?);
+
+    let peer_channel = match config.peer_channel_kind {
+        PeerChannelKind::Plain => None,
+        PeerChannelKind::Authenticated => {
+            let orchestrator_public_key_path = config.orchestrator_public_key_path.ok_or_else(|| {
+                eyre::eyre!("orchestrator_public_key_path is required when peer_channel_kind is authenticated")
+            })?;
+            let orchestrator_public_key = crypto_box::PublicKey::from_slice(&std::fs::read(
+                orchestrator_public_key_path,
+            )?)?;
+            Some(Arc::new(crypto_box::SalsaBox::new(
+                &orchestrator_public_key,
+                crypto_device.secret_key(),
+            )))
+        }
+    };
 
     let proof_schema =
         serde_json::from_reader(File::open(&config.oblivious_map_read_proof_schema_path)?)?;
@@ -60,14 +109,58 @@ pub async fn start(config: BitservicePeerConfig) -> eyre::Result<()> {
             config.next_peer,
             config.tcp_next_peer,
             config.prev_peer_wait_timeout,
+            config.ws_idle_timeout,
+            config.ws_ping_interval,
+            config.ban_batch_max_size,
+            config.ban_batch_max_delay,
+            config.transport_kind,
+            config.next_peer_public_key_path,
+            config.prev_peer_public_key_path,
+            config.tcp_identity_key_path,
+            config.next_peer_tcp_identity_key_path,
+            config.prev_peer_tcp_identity_key_path,
+            config.tcp_network_key_path,
             read_groth16,
             write_groth16,
             crypto_device,
             db,
         )
         .await?,
+        peer_channel,
+        rate_limiter: RateLimiter::new(RateLimiterConfig {
+            burst: config.rate_limit_burst,
+            refill_per_sec: config.rate_limit_per_sec,
+            idle_ttl: Duration::from_secs(300),
+            reap_interval: Duration::from_secs(60),
+        }),
+        trust_forwarded_for: config.trust_forwarded_for,
     };
-    let app = api::new_app(app_state);
+    if let PeerTransportKind::Reverse = config.peer_transport_kind {
+        let relay_url = config
+            .relay_url
+            .clone()
+            .ok_or_else(|| eyre::eyre!("relay_url is required when peer_transport_kind is reverse"))?;
+        let relay_peer_id = config.relay_peer_id.clone().ok_or_else(|| {
+            eyre::eyre!("relay_peer_id is required when peer_transport_kind is reverse")
+        })?;
+        let relay_shared_secret = config.relay_shared_secret.clone().ok_or_else(|| {
+            eyre::eyre!("relay_shared_secret is required when peer_transport_kind is reverse")
+        })?;
+        let relay_state = app_state.clone();
+        let relay_request_timeout = config.relay_request_timeout;
+        tokio::spawn(async move {
+            relay_client::run(
+                relay_state,
+                relay_url,
+                relay_peer_id,
+                relay_shared_secret,
+                relay_request_timeout,
+            )
+            .await;
+        });
+    }
+
+    let app = api::new_app(app_state.clone());
 
     let listener = tokio::net::TcpListener::bind(config.bind_addr)
         .await?
@@ -78,7 +171,45 @@ pub async fn start(config: BitservicePeerConfig) -> eyre::Result<()> {
         });
     tracing::info!("starting axum server on {}", config.bind_addr);
 
-    axum::serve(listener, app).await?;
+    let ban_service = app_state.ban_service.clone();
+    let shutdown_drain_timeout = config.shutdown_drain_timeout;
+
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+    )
+    .with_graceful_shutdown(async move {
+        wait_for_shutdown_signal().await;
+        tracing::info!("shutdown signal received, draining in-flight requests");
+        ban_service.shutdown(shutdown_drain_timeout).await;
+    })
+    .await?;
 
     Ok(())
 }
+
+/// Resolves on SIGINT (ctrl-c) or, on unix, SIGTERM - whichever a process manager or operator
+/// sends to ask us to stop.
+async fn wait_for_shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("failed to install SIGINT handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
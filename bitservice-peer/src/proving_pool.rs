@@ -0,0 +1,56 @@
+use std::sync::Arc;
+
+use tokio::sync::{Semaphore, oneshot};
+
+/// A dedicated CPU-bound worker pool for the Groth16/rep3 proving done by
+/// [`crate::ban_service::BanService`]'s `read`/`ban`/`unban`/`prune`.
+///
+/// Those calls used to run inside `tokio::task::block_in_place`, which parks a tokio worker
+/// thread for the full duration of an oblivious linear scan plus proof - under concurrent load
+/// that starves the same reactor that's driving the `init_ws_mpc_net` traffic for those very
+/// requests. Running the proving on this pool instead keeps the reactor free, and the
+/// `permits` semaphore bounds how much work can be in flight: once every worker is busy,
+/// [`ProvingPool::spawn`] fails immediately instead of queueing without bound, so the caller can
+/// surface a clean overload signal rather than letting requests pile up unboundedly.
+#[derive(Clone)]
+pub(crate) struct ProvingPool {
+    pool: Arc<rayon::ThreadPool>,
+    permits: Arc<Semaphore>,
+}
+
+impl ProvingPool {
+    /// Builds a pool sized to the machine's physical cores.
+    pub(crate) fn new() -> eyre::Result<Self> {
+        let workers = num_cpus::get_physical().max(1);
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(workers)
+            .thread_name(|i| format!("bitservice-peer-proving-{i}"))
+            .build()?;
+        Ok(Self {
+            pool: Arc::new(pool),
+            permits: Arc::new(Semaphore::new(workers)),
+        })
+    }
+
+    /// Runs `f` on the pool and awaits its result, without blocking a tokio worker thread.
+    ///
+    /// Fails immediately, instead of queueing `f` for later, if every worker is already busy -
+    /// callers should surface this as an overload/503 rather than retrying internally.
+    pub(crate) async fn spawn<F, T>(&self, f: F) -> eyre::Result<T>
+    where
+        F: FnOnce() -> eyre::Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let permit = Arc::clone(&self.permits)
+            .try_acquire_owned()
+            .map_err(|_| eyre::eyre!("proving pool is overloaded, try again later"))?;
+
+        let (tx, rx) = oneshot::channel();
+        self.pool.spawn(move || {
+            let _permit = permit;
+            let _ = tx.send(f());
+        });
+
+        rx.await.map_err(|_| eyre::eyre!("proving worker panicked"))?
+    }
+}
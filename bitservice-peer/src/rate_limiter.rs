@@ -0,0 +1,84 @@
+//! Per-client-IP token-bucket rate limiting for the v1 API, so a single abusive client can't
+//! starve the oblivious map read/write path for everyone else. Applied by
+//! [`crate::api::rate_limit::guard`].
+
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use tokio::sync::Mutex;
+
+/// Tunables for [`RateLimiter`]: `burst` tokens are available immediately, refilling at
+/// `refill_per_sec` tokens/second up to `burst` again.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RateLimiterConfig {
+    pub(crate) burst: u32,
+    pub(crate) refill_per_sec: f64,
+    /// How long an IP's bucket may sit untouched before the reaper drops it, so a one-off
+    /// caller doesn't keep a slot in the table forever.
+    pub(crate) idle_ttl: Duration,
+    /// How often the reaper sweeps the bucket table for idle entries.
+    pub(crate) reap_interval: Duration,
+}
+
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+/// A token bucket per client IP, shared across clones like [`crate::ban_service::BanService`] and
+/// the rest of this crate's `Arc`-backed state.
+#[derive(Clone)]
+pub(crate) struct RateLimiter {
+    config: RateLimiterConfig,
+    buckets: Arc<Mutex<HashMap<IpAddr, TokenBucket>>>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(config: RateLimiterConfig) -> Self {
+        let limiter = Self {
+            config,
+            buckets: Arc::default(),
+        };
+
+        let limiter_clone = limiter.clone();
+        tokio::spawn(async move {
+            limiter_clone.reap().await;
+        });
+
+        limiter
+    }
+
+    /// Periodically evicts buckets that haven't been touched in `config.idle_ttl`, so the table
+    /// doesn't grow without bound as distinct client IPs come and go.
+    async fn reap(&self) {
+        let mut interval = tokio::time::interval(self.config.reap_interval);
+        loop {
+            interval.tick().await;
+            let mut buckets = self.buckets.lock().await;
+            buckets.retain(|_, bucket| bucket.last_refill.elapsed() <= self.config.idle_ttl);
+        }
+    }
+
+    /// Withdraws one token from `ip`'s bucket, refilling it for elapsed time first. Returns
+    /// whether the request is admitted.
+    pub(crate) async fn try_acquire(&self, ip: IpAddr) -> bool {
+        let mut buckets = self.buckets.lock().await;
+        let bucket = buckets.entry(ip).or_insert_with(|| TokenBucket {
+            tokens: self.config.burst as f64,
+            last_refill: Instant::now(),
+        });
+        let elapsed = bucket.last_refill.elapsed().as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.config.refill_per_sec).min(self.config.burst as f64);
+        bucket.last_refill = Instant::now();
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
@@ -0,0 +1,205 @@
+//! Dial-out loop for peers configured with `peer_transport_kind: reverse`: instead of waiting
+//! for the orchestrator to POST requests in, this peer connects out to the orchestrator's
+//! `/relay/{peer_id}` endpoint and serves `read`/`ban`/`unban`/`prune` requests handed to it over
+//! that persistent connection. See `bitservice_types::relay` for the wire format and
+//! `bitservice_server::relay::RelayHub` for the orchestrator side.
+
+use std::time::Duration;
+
+use bitservice_types::{
+    ban::{PeerBanRequest, PeerBanResponse},
+    peer_channel::SealedEnvelope,
+    prune::{PeerPruneRequest, PeerPruneResponse},
+    read::{PeerReadRequest, PeerReadResponse},
+    relay::{RELAY_SHARED_SECRET_HEADER, RelayFrame, RelayOp},
+    unban::{PeerUnbanRequest, PeerUnbanResponse},
+};
+use crypto_box::SalsaBox;
+use eyre::Context as _;
+use futures::{SinkExt as _, StreamExt as _};
+use http::HeaderValue;
+use secrecy::{ExposeSecret as _, SecretString};
+use serde::{Serialize, de::DeserializeOwned};
+use tokio_tungstenite::tungstenite::client::IntoClientRequest as _;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream, tungstenite::Message};
+use uuid::Uuid;
+
+use crate::AppState;
+
+/// Backoff schedule for redialing the orchestrator's relay endpoint after a disconnect. Mirrors
+/// `ws_mpc_net::ReconnectConfig`; kept separate since that crate's retry loop isn't `pub`.
+#[derive(Debug, Clone, Copy)]
+struct ReconnectConfig {
+    initial_backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl Default for ReconnectConfig {
+    fn default() -> Self {
+        Self {
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Runs forever, dialing `relay_url`'s `/relay/{peer_id}` endpoint and serving requests over it,
+/// redialing with backoff whenever the connection drops or fails to establish.
+pub(crate) async fn run(
+    state: AppState,
+    relay_url: String,
+    peer_id: String,
+    shared_secret: SecretString,
+    request_timeout: Duration,
+) {
+    let config = ReconnectConfig::default();
+    let mut backoff = config.initial_backoff;
+    loop {
+        match dial(&relay_url, &peer_id, &shared_secret).await {
+            Ok(stream) => {
+                backoff = config.initial_backoff;
+                tracing::info!("connected to relay at {relay_url} as {peer_id}");
+                if let Err(err) = serve(&state, stream, request_timeout).await {
+                    tracing::warn!("relay connection to {relay_url} dropped: {err:?}");
+                }
+            }
+            Err(err) => {
+                tracing::warn!(
+                    "failed to connect to relay at {relay_url}: {err:?}, retrying in {backoff:?}"
+                );
+            }
+        }
+        tokio::time::sleep(backoff).await;
+        backoff = (backoff * 2).min(config.max_backoff);
+    }
+}
+
+async fn dial(
+    relay_url: &str,
+    peer_id: &str,
+    shared_secret: &SecretString,
+) -> eyre::Result<WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>> {
+    let url = format!("{relay_url}/relay/{peer_id}");
+    let mut request = url
+        .into_client_request()
+        .context("while building relay connect request")?;
+    request.headers_mut().insert(
+        RELAY_SHARED_SECRET_HEADER,
+        HeaderValue::from_str(shared_secret.expose_secret())
+            .context("relay_shared_secret is not a valid header value")?,
+    );
+    let (stream, _) = tokio_tungstenite::connect_async(request)
+        .await
+        .context("while connecting to relay endpoint")?;
+    Ok(stream)
+}
+
+/// Serves requests over one connected relay session until it errors or the orchestrator closes
+/// it. Every frame this peer never sent unprompted, so every frame received here is treated as a
+/// request tagged with the `op` to dispatch it to.
+async fn serve(
+    state: &AppState,
+    stream: WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>,
+    request_timeout: Duration,
+) -> eyre::Result<()> {
+    let (mut sink, mut stream) = stream.split();
+    while let Some(message) = stream.next().await {
+        let message = message.context("relay connection error")?;
+        let Message::Binary(data) = message else {
+            continue;
+        };
+        let frame: RelayFrame = serde_json::from_slice(&data).context("malformed relay frame")?;
+        let Some(op) = frame.op else {
+            tracing::warn!(
+                "dropping relay frame without an op (request_id {})",
+                frame.request_id
+            );
+            continue;
+        };
+        let request_id = frame.request_id;
+        let body = match tokio::time::timeout(
+            request_timeout,
+            dispatch(state, op, request_id, frame.body),
+        )
+        .await
+        {
+            Ok(Ok(body)) => body,
+            Ok(Err(err)) => {
+                tracing::warn!("relay request {request_id} failed: {err:?}");
+                continue;
+            }
+            Err(_) => {
+                tracing::warn!("relay request {request_id} timed out");
+                continue;
+            }
+        };
+        let response = RelayFrame {
+            request_id,
+            op: None,
+            body,
+        };
+        let encoded = serde_json::to_vec(&response).context("failed to encode relay response")?;
+        sink.send(Message::Binary(encoded.into()))
+            .await
+            .context("relay connection error")?;
+    }
+    Ok(())
+}
+
+/// Unseals and deserializes `body`, then hands it to the matching [`crate::ban_service::BanService`]
+/// method, and seals the encoded response back - the same steps `api::peer_channel::guard` and
+/// `api::v1`'s handlers apply to an HTTP request/response, just without an axum request/response
+/// in between.
+async fn dispatch(state: &AppState, op: RelayOp, request_id: Uuid, body: Vec<u8>) -> eyre::Result<Vec<u8>> {
+    let channel = state.peer_channel.as_deref();
+    match op {
+        RelayOp::Read => {
+            let req: PeerReadRequest = open_body(&body, channel)?;
+            let res = state.ban_service.read(req, request_id).await?;
+            seal_body(&res, channel)
+        }
+        RelayOp::Ban => {
+            let req: PeerBanRequest = open_body(&body, channel)?;
+            let res = state.ban_service.ban(req, request_id).await?;
+            seal_body(&res, channel)
+        }
+        RelayOp::Unban => {
+            let req: PeerUnbanRequest = open_body(&body, channel)?;
+            let res = state.ban_service.unban(req, request_id).await?;
+            seal_body(&res, channel)
+        }
+        RelayOp::Prune => {
+            let _req: PeerPruneRequest = open_body(&body, channel)?;
+            state.ban_service.prune(request_id).await?;
+            seal_body(&PeerPruneResponse {}, channel)
+        }
+    }
+}
+
+/// Reverses [`seal_body`]: opens `bytes` under `channel` when this peer has one configured, then
+/// deserializes the resulting JSON.
+fn open_body<Req: DeserializeOwned>(bytes: &[u8], channel: Option<&SalsaBox>) -> eyre::Result<Req> {
+    let plaintext = match channel {
+        None => bytes.to_vec(),
+        Some(channel) => {
+            let envelope: SealedEnvelope =
+                serde_json::from_slice(bytes).context("relay request is not a sealed envelope")?;
+            envelope
+                .open(channel)
+                .map_err(|_| eyre::eyre!("failed to authenticate sealed relay request"))?
+        }
+    };
+    Ok(serde_json::from_slice(&plaintext)?)
+}
+
+/// Serializes `value` to JSON, sealing it under `channel` when this peer has one configured.
+fn seal_body(value: &impl Serialize, channel: Option<&SalsaBox>) -> eyre::Result<Vec<u8>> {
+    let plaintext = serde_json::to_vec(value).context("failed to serialize relay response body")?;
+    match channel {
+        None => Ok(plaintext),
+        Some(channel) => {
+            let sealed = SealedEnvelope::seal(channel, &plaintext, &mut rand::thread_rng());
+            Ok(serde_json::to_vec(&sealed).context("failed to serialize sealed envelope")?)
+        }
+    }
+}
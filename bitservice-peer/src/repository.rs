@@ -1,3 +1,5 @@
+use ark_serialize::{CanonicalDeserialize as _, CanonicalSerialize as _};
+use bitservice_types::mmr::Mmr;
 use eyre::Context as _;
 use oblivious_linear_scan_map::{Groth16Material, LinearScanObliviousMap};
 use sqlx::{PgPool, Row, migrate::Migrator, postgres::PgPoolOptions};
@@ -32,42 +34,186 @@ impl DbPool {
         let row = sqlx::query("SELECT data FROM map WHERE id = 0")
             .fetch_optional(&self.pool)
             .await?;
-        if let Some(row) = row {
-            tracing::debug!("loading map from db");
-            let data = row.get::<Vec<u8>, _>("data");
-            let oblivious_map = LinearScanObliviousMap::from_dump(
-                data.as_slice(),
-                ark_serialize::Compress::No,
-                ark_serialize::Validate::No,
-                read_groth16,
-                write_groth16,
-            )?;
-            Ok(oblivious_map)
-        } else {
+        let Some(row) = row else {
             tracing::debug!("init empty map in db");
             let oblivious_map = LinearScanObliviousMap::new(read_groth16, write_groth16);
-            self.store_map(&oblivious_map).await?;
-            Ok(oblivious_map)
-        }
+            self.store_snapshot(&oblivious_map, 0).await?;
+            return Ok(oblivious_map);
+        };
+
+        tracing::debug!("loading map snapshot from db");
+        let data = row.get::<Vec<u8>, _>("data");
+        let oblivious_map = LinearScanObliviousMap::from_dump(
+            data.as_slice(),
+            ark_serialize::Compress::No,
+            ark_serialize::Validate::No,
+            read_groth16,
+            write_groth16,
+        )?;
+
+        // `append_operation` snapshots the whole map on every committed ban/unban, so `map`
+        // always reflects the last one that finished - there's no gap of un-replayable
+        // operations to re-snapshot past here, unlike the old `SNAPSHOT_INTERVAL` scheme.
+        Ok(oblivious_map)
+    }
+
+    /// Reconstructs `root_mmr` from every leaf ever appended to `mmr_log`, in version order.
+    /// `Mmr` has no peaks-only (de)serialization, so replaying each leaf via [`rebuild_mmr`] is
+    /// the only way to recover an equivalent accumulator after a restart.
+    pub(crate) async fn load_mmr(&self) -> eyre::Result<Mmr> {
+        let rows = sqlx::query("SELECT leaf FROM mmr_log ORDER BY version ASC")
+            .fetch_all(&self.pool)
+            .await?;
+        let leaves = rows
+            .into_iter()
+            .map(|row| {
+                let bytes = row.get::<Vec<u8>, _>("leaf");
+                ark_bn254::Fr::deserialize_compressed(bytes.as_slice()).map_err(eyre::Error::from)
+            })
+            .collect::<eyre::Result<Vec<_>>>()?;
+        Ok(rebuild_mmr(leaves))
+    }
+
+    /// Persists `new_root` to `mmr_log` and `map_data` to `map`, both under the next version, in
+    /// one transaction - so a crash between two committed writes can never discard one: by the
+    /// time `append_operation` returns, `map` already reflects it. This used to snapshot only
+    /// every `SNAPSHOT_INTERVAL` versions and log just the root transition the rest of the time,
+    /// but `oblivious_linear_scan_map` only exposes whole-map `dump`/`from_dump`, not a
+    /// delta/patch format, so a root-only log entry could never actually be replayed - a crash
+    /// before the next snapshot silently lost every write since the last one. Snapshotting every
+    /// write is O(map size) per write instead of O(1), but is the only way to make every
+    /// committed write durable with what this map exposes.
+    ///
+    /// `map_data` must be a `dump` of the map taken at the point `new_root` was produced, not
+    /// whatever the caller's map has mutated to by the time this is called - when persisting a
+    /// batch of ops applied to one shared map in sequence, that means dumping right after each
+    /// op runs, before the next one mutates it further, rather than dumping once after the whole
+    /// batch and reusing it for every op's `append_operation` call.
+    pub(crate) async fn append_operation(
+        &self,
+        map_data: Vec<u8>,
+        new_root: ark_bn254::Fr,
+    ) -> eyre::Result<()> {
+        let mut new_root_bytes = Vec::new();
+        new_root.serialize_compressed(&mut new_root_bytes)?;
+
+        let mut tx = self.pool.begin().await?;
+
+        let version: i64 = sqlx::query("SELECT COALESCE((SELECT version FROM map WHERE id = 0), 0) + 1")
+            .fetch_one(&mut *tx)
+            .await?
+            .get(0);
+
+        // Never compacted - see `load_mmr`.
+        sqlx::query("INSERT INTO mmr_log (version, leaf) VALUES ($1, $2)")
+            .bind(version)
+            .bind(new_root_bytes)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query(
+            "
+            INSERT INTO map (id, data, version)
+            VALUES (0, $1, $2)
+            ON CONFLICT(id)
+            DO UPDATE SET data = EXCLUDED.data, version = EXCLUDED.version;
+            ",
+        )
+        .bind(map_data)
+        .bind(version)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
     }
 
-    pub(crate) async fn store_map(
+    /// Persists a full `dump` of `oblivious_map` under the next version. Used by `prune`, which
+    /// already rewrites the whole map, so there's no cheaper incremental representation of that
+    /// operation to log.
+    pub(crate) async fn force_snapshot(
         &self,
         oblivious_map: &LinearScanObliviousMap,
     ) -> eyre::Result<()> {
+        let mut tx = self.pool.begin().await?;
+        let version: i64 = sqlx::query("SELECT COALESCE((SELECT version FROM map WHERE id = 0), 0) + 1")
+            .fetch_one(&mut *tx)
+            .await?
+            .get(0);
+
         let mut data = Vec::new();
         oblivious_map.dump(&mut data, ark_serialize::Compress::No)?;
         sqlx::query(
             "
-            INSERT INTO map (id, data)
-            VALUES (0, $1)
+            INSERT INTO map (id, data, version)
+            VALUES (0, $1, $2)
             ON CONFLICT(id)
-            DO UPDATE SET data = EXCLUDED.data;
+            DO UPDATE SET data = EXCLUDED.data, version = EXCLUDED.version;
             ",
         )
         .bind(data)
+        .bind(version)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Unconditionally persists a full `dump` of `oblivious_map` as the snapshot for `version`.
+    /// Used only to initialize an empty map.
+    async fn store_snapshot(
+        &self,
+        oblivious_map: &LinearScanObliviousMap,
+        version: i64,
+    ) -> eyre::Result<()> {
+        let mut data = Vec::new();
+        oblivious_map.dump(&mut data, ark_serialize::Compress::No)?;
+        sqlx::query(
+            "
+            INSERT INTO map (id, data, version)
+            VALUES (0, $1, $2)
+            ON CONFLICT(id)
+            DO UPDATE SET data = EXCLUDED.data, version = EXCLUDED.version;
+            ",
+        )
+        .bind(data)
+        .bind(version)
         .execute(&self.pool)
         .await?;
         Ok(())
     }
 }
+
+/// Builds an [`Mmr`] by pushing `leaves` in order - the only way to reconstruct one, since it has
+/// no peaks-only (de)serialization. Factored out of [`DbPool::load_mmr`] so it's unit-testable
+/// without a live Postgres connection.
+fn rebuild_mmr(leaves: impl IntoIterator<Item = ark_bn254::Fr>) -> Mmr {
+    let mut mmr = Mmr::new();
+    for leaf in leaves {
+        mmr.push(leaf);
+    }
+    mmr
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ff::UniformRand as _;
+
+    use super::*;
+
+    #[test]
+    fn rebuild_mmr_matches_incremental_pushes() {
+        let mut rng = rand::thread_rng();
+        let leaves: Vec<ark_bn254::Fr> = (0..5).map(|_| ark_bn254::Fr::rand(&mut rng)).collect();
+
+        let mut incremental = Mmr::new();
+        for leaf in &leaves {
+            incremental.push(*leaf);
+        }
+
+        let rebuilt = rebuild_mmr(leaves);
+
+        assert_eq!(rebuilt.len(), incremental.len());
+        assert_eq!(rebuilt.root(), incremental.root());
+    }
+}
@@ -0,0 +1,84 @@
+use std::path::PathBuf;
+
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use secrecy::SecretBox;
+
+/// Supplies the 32 bytes of `CryptoDevice`'s long-term secret key, decoupling where the key
+/// material actually lives from how `CryptoDevice` uses it. Selected once at startup via
+/// `SecretBackend`/`BitservicePeerConfig`, and dispatched through [`SecretProviderKind`].
+pub(crate) trait SecretProvider {
+    async fn load_secret_key(&self) -> eyre::Result<SecretBox<[u8; 32]>>;
+}
+
+/// Reads the key straight off local disk - the original, and still default, behavior.
+pub(crate) struct FileSecretProvider {
+    path: PathBuf,
+}
+
+impl FileSecretProvider {
+    pub(crate) fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+impl SecretProvider for FileSecretProvider {
+    async fn load_secret_key(&self) -> eyre::Result<SecretBox<[u8; 32]>> {
+        let bytes = tokio::fs::read(&self.path).await?;
+        let bytes: [u8; 32] = bytes
+            .try_into()
+            .map_err(|_| eyre::eyre!("secret key must be 32 bytes"))?;
+        Ok(SecretBox::new(Box::new(bytes)))
+    }
+}
+
+/// Fetches the key from AWS Secrets Manager by secret id at startup instead of reading it off
+/// a local filesystem, base64-decoding its `SecretString` value.
+pub(crate) struct AwsSecretsManagerProvider {
+    secret_id: String,
+    client: aws_sdk_secretsmanager::Client,
+}
+
+impl AwsSecretsManagerProvider {
+    pub(crate) async fn new(secret_id: String) -> eyre::Result<Self> {
+        let sdk_config = aws_config::load_defaults(aws_config::BehaviorVersion::latest()).await;
+        Ok(Self {
+            secret_id,
+            client: aws_sdk_secretsmanager::Client::new(&sdk_config),
+        })
+    }
+}
+
+impl SecretProvider for AwsSecretsManagerProvider {
+    async fn load_secret_key(&self) -> eyre::Result<SecretBox<[u8; 32]>> {
+        let output = self
+            .client
+            .get_secret_value()
+            .secret_id(&self.secret_id)
+            .send()
+            .await?;
+        let encoded = output.secret_string().ok_or_else(|| {
+            eyre::eyre!("secret {} has no SecretString value", self.secret_id)
+        })?;
+        let bytes = STANDARD.decode(encoded)?;
+        let bytes: [u8; 32] = bytes
+            .try_into()
+            .map_err(|_| eyre::eyre!("secret key must be 32 bytes"))?;
+        Ok(SecretBox::new(Box::new(bytes)))
+    }
+}
+
+/// The configured [`SecretProvider`] backend, matching the enum-dispatch pattern already used
+/// for `PeerNetwork`/`PeerTcpNetwork` rather than a trait object.
+pub(crate) enum SecretProviderKind {
+    File(FileSecretProvider),
+    AwsSecretsManager(AwsSecretsManagerProvider),
+}
+
+impl SecretProviderKind {
+    pub(crate) async fn load_secret_key(&self) -> eyre::Result<SecretBox<[u8; 32]>> {
+        match self {
+            Self::File(provider) => provider.load_secret_key().await,
+            Self::AwsSecretsManager(provider) => provider.load_secret_key().await,
+        }
+    }
+}
@@ -0,0 +1,38 @@
+//! API module for the bitservice server.
+//!
+//! This module defines all HTTP/WebSocket endpoints exposed by the bitservice server and
+//! organizes them into submodules:
+//!
+//! - [`errors`] - Defines API error types and conversions from internal service errors.
+//! - [`v1`] - Version 1 of the endpoints, including `/read`, `/ban`, `/unban` and `/subscribe`.
+//! - [`relay`] - The `/relay/{peer_id}` dial-in endpoint for peers using the reverse transport.
+
+use axum::Router;
+use metrics_exporter_prometheus::PrometheusHandle;
+use tower_http::trace::TraceLayer;
+
+use crate::AppState;
+
+pub(crate) mod errors;
+pub(crate) mod metrics;
+pub(crate) mod relay;
+pub(crate) mod v1;
+
+/// Builds the main API router for the bitservice server.
+///
+/// This function sets up:
+///
+/// - The `/api/v1` endpoints from [`v1`].
+/// - The `/metrics` Prometheus scrape endpoint from [`metrics`].
+/// - An HTTP trace layer via [`TraceLayer`].
+///
+/// The returned [`Router`] has an [`AppState`] attached that contains the per-rp services needed
+/// to handle requests.
+pub(crate) fn new_app(app_state: AppState, metrics_handle: PrometheusHandle) -> Router {
+    Router::new()
+        .nest("/api/v1", v1::build())
+        .merge(metrics::routes(metrics_handle))
+        .merge(relay::routes())
+        .layer(TraceLayer::new_for_http())
+        .with_state(app_state)
+}
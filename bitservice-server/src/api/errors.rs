@@ -0,0 +1,29 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+
+/// Errors the v1 API can return, mapped to HTTP status codes by [`IntoResponse`].
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum ApiErrors {
+    /// The requested rp_id (or similar path component) is not known to this instance.
+    #[error("not found: {0}")]
+    NotFound(String),
+    /// Something went wrong talking to the peers.
+    #[error(transparent)]
+    Internal(#[from] eyre::Report),
+}
+
+impl IntoResponse for ApiErrors {
+    fn into_response(self) -> Response {
+        match self {
+            ApiErrors::NotFound(msg) => (StatusCode::NOT_FOUND, msg).into_response(),
+            ApiErrors::Internal(err) => {
+                tracing::error!("internal error handling request: {err:?}");
+                (StatusCode::INTERNAL_SERVER_ERROR, "internal error").into_response()
+            }
+        }
+    }
+}
+
+pub(crate) type ApiResult<T> = Result<T, ApiErrors>;
@@ -0,0 +1,10 @@
+//! Exposes the process's Prometheus metrics for scraping.
+
+use axum::{Router, routing::get};
+use metrics_exporter_prometheus::PrometheusHandle;
+
+/// Builds the `/metrics` route, rendering whatever [`crate::metrics::install_recorder`]'s
+/// handle has recorded so far.
+pub(crate) fn routes(handle: PrometheusHandle) -> Router {
+    Router::new().route("/metrics", get(move || async move { handle.render() }))
+}
@@ -0,0 +1,37 @@
+//! Dial-in endpoint a peer connects to when its `transport_kind` is `reverse`: the peer parks a
+//! long-lived websocket here under its configured `peer_id`, and [`crate::relay::RelayHub`] hands
+//! `read`/`ban`/`unban`/`prune` requests off over it in place of an HTTP POST.
+
+use axum::{
+    Router,
+    extract::{Path, State, WebSocketUpgrade},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::any,
+};
+use bitservice_types::relay::RELAY_SHARED_SECRET_HEADER;
+
+use crate::AppState;
+
+pub(crate) fn routes() -> Router<AppState> {
+    Router::new().route("/relay/{peer_id}", any(relay))
+}
+
+async fn relay(
+    State(state): State<AppState>,
+    Path(peer_id): Path<String>,
+    headers: HeaderMap,
+    ws: WebSocketUpgrade,
+) -> Response {
+    let credential = headers
+        .get(RELAY_SHARED_SECRET_HEADER)
+        .and_then(|value| value.to_str().ok());
+    if !state.relay_hub.authorize(credential) {
+        tracing::warn!("rejected relay dial-in for peer {peer_id}: missing or wrong shared secret");
+        return (StatusCode::UNAUTHORIZED, "invalid relay credential").into_response();
+    }
+
+    ws.on_upgrade(move |socket| async move {
+        state.relay_hub.serve(peer_id, socket).await;
+    })
+}
@@ -6,19 +6,25 @@
 //! It also applies a restrictive CORS policy suitable for JSON-based POST requests.
 use axum::{
     Json, Router,
-    extract::{Path, State},
-    routing::post,
+    extract::{
+        Path, State,
+        ws::{Message, WebSocket, WebSocketUpgrade},
+    },
+    response::Response,
+    routing::{any, post},
 };
 use bitservice_types::{
     ban::{BanRequest, BanResponse},
     read::{ReadRequest, ReadResponse},
     unban::{UnbanRequest, UnbanResponse},
 };
+use tokio::sync::broadcast;
 use tracing::instrument;
 
 use crate::{
     AppState,
     api::errors::{ApiErrors, ApiResult},
+    events::Event,
 };
 
 /// Build the v1 API router.
@@ -37,6 +43,7 @@ pub(crate) fn build() -> Router<AppState> {
         .route("/read/{rp_id}", post(read))
         .route("/ban/{rp_id}", post(ban))
         .route("/unban/{rp_id}", post(unban))
+        .route("/subscribe/{rp_id}", any(subscribe))
 }
 
 #[instrument(level = "debug", skip_all, fields(rp_id = rp_id))]
@@ -92,3 +99,44 @@ async fn unban(
 
     Ok(Json(res))
 }
+
+/// Upgrades to a websocket that streams [`crate::events::Event`]s for `rp_id` - `RootAdvanced`
+/// and `Banned` - as `RpRwQueue` commits writes, so callers like indexers don't have to poll
+/// `/read` to notice a change.
+#[instrument(level = "debug", skip_all, fields(rp_id = rp_id))]
+async fn subscribe(
+    State(state): State<AppState>,
+    Path(rp_id): Path<u128>,
+    ws: WebSocketUpgrade,
+) -> ApiResult<Response> {
+    let rp_bitservice = state
+        .rp_bitservices
+        .get(&rp_id)
+        .ok_or_else(|| ApiErrors::NotFound(format!("unknown rp_id: {rp_id}")))?;
+
+    let rx = rp_bitservice.events.subscribe();
+    Ok(ws.on_upgrade(move |socket| stream_events(socket, rx)))
+}
+
+/// Pushes every event received on `rx` to `socket` as a JSON text frame, until the socket closes.
+///
+/// A subscriber that falls behind gets a `{"type":"Lagged","skipped":N}` frame instead of
+/// stalling `RpRwQueue` - see [`EventBus`](crate::events::EventBus).
+async fn stream_events(mut socket: WebSocket, mut rx: broadcast::Receiver<Event>) {
+    loop {
+        let message = match rx.recv().await {
+            Ok(event) => serde_json::to_string(&event),
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                tracing::warn!("subscriber lagged, skipped {skipped} events");
+                serde_json::to_string(&serde_json::json!({"type": "Lagged", "skipped": skipped}))
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+        let Ok(message) = message else {
+            continue;
+        };
+        if socket.send(Message::Text(message.into())).await.is_err() {
+            break;
+        }
+    }
+}
@@ -8,6 +8,7 @@ use git_version::git_version;
 async fn main() -> eyre::Result<ExitCode> {
     let tracing_config = nodes_telemetry::TracingConfig::try_from_env()?;
     let _tracing_handle = nodes_telemetry::initialize_tracing(&tracing_config)?;
+    let metrics_handle = bitservice_server::metrics::install_recorder()?;
     bitservice_server::metrics::describe_metrics();
     tracing::info!(
         "{} {} ({})",
@@ -16,7 +17,7 @@ async fn main() -> eyre::Result<ExitCode> {
         option_env!("GIT_HASH").unwrap_or(git_version!(fallback = "UNKNOWN"))
     );
 
-    let result = bitservice_server::start(BitserviceServerConfig::parse()).await;
+    let result = bitservice_server::start(BitserviceServerConfig::parse(), metrics_handle).await;
     match result {
         Ok(()) => {
             tracing::info!("good night!");
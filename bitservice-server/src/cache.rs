@@ -0,0 +1,190 @@
+//! Read-through cache sitting in front of [`crate::rw_queue::RpRwQueue`]'s read path.
+//!
+//! Repeated [`ReadRequest`]s for the same peer-sealed shares re-run the full MPC read and its
+//! Groth16 proof on every call, which is wasted work once a result has already been produced for
+//! the rp's current root. [`RpRwQueue`](crate::rw_queue::RpRwQueue) keys each response by the
+//! request's sealed shares *and* the root it was answered against (see [`CacheKey`]), so a write
+//! or prune that advances the root doesn't even need to race the cache: entries from before it
+//! are keyed under a root nothing will ever look up again. [`CacheAdapter::invalidate_rp`] drops
+//! them anyway, both to reclaim space and to cover prunes, which don't hand back a new root to key
+//! under.
+//!
+//! Two [`CacheAdapter`]s are provided: [`InMemoryCache`], an embedded LRU+TTL store for a single
+//! instance, and [`RedisCache`], so horizontally-scaled instances can share one cache.
+
+use std::{sync::Arc, time::Duration};
+
+use ark_ff::{BigInteger, PrimeField};
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use bitservice_types::read::ReadResponse;
+use redis::AsyncCommands;
+
+/// Identifies one cached [`ReadResponse`]: the rp it belongs to, the three peers' sealed shares
+/// from the original [`PeerReadRequest`](bitservice_types::read::PeerReadRequest)s, and the root
+/// it was answered against.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub(crate) struct CacheKey {
+    rp_id: u128,
+    sealed_keys: [String; 3],
+    root: Vec<u8>,
+}
+
+impl CacheKey {
+    pub(crate) fn new(rp_id: u128, sealed_keys: [String; 3], root: ark_bn254::Fr) -> Self {
+        Self {
+            rp_id,
+            sealed_keys,
+            root: root.into_bigint().to_bytes_be(),
+        }
+    }
+}
+
+/// Backing store for the read-through cache.
+///
+/// Implementations only need to behave like a TTL-expiring key/value store plus one bulk
+/// operation, dropping every entry for an `rp_id` regardless of the root it was cached under -
+/// [`RpRwQueue`](crate::rw_queue::RpRwQueue) owns cache-key construction and decides when that
+/// bulk drop is needed.
+pub(crate) trait CacheAdapter: Send + Sync {
+    /// Looks up a previously cached response, or `None` on a miss or expiry.
+    async fn get(&self, key: &CacheKey) -> Option<ReadResponse>;
+
+    /// Stores `value` under `key`, expiring it after `ttl`.
+    async fn put(&self, key: CacheKey, value: ReadResponse, ttl: Duration);
+
+    /// Drops every cached entry belonging to `rp_id`.
+    async fn invalidate_rp(&self, rp_id: u128);
+}
+
+/// Embedded in-memory [`CacheAdapter`], bounded by both entry count (LRU eviction) and TTL.
+pub(crate) struct InMemoryCache {
+    cache: moka::future::Cache<CacheKey, ReadResponse>,
+}
+
+impl InMemoryCache {
+    pub(crate) fn new(max_capacity: u64, ttl: Duration) -> Self {
+        Self {
+            cache: moka::future::Cache::builder()
+                .max_capacity(max_capacity)
+                .time_to_live(ttl)
+                .build(),
+        }
+    }
+}
+
+impl CacheAdapter for InMemoryCache {
+    async fn get(&self, key: &CacheKey) -> Option<ReadResponse> {
+        self.cache.get(key).await
+    }
+
+    async fn put(&self, key: CacheKey, value: ReadResponse, _ttl: Duration) {
+        // the cache-wide TTL set in `new` already covers every entry
+        self.cache.insert(key, value).await;
+    }
+
+    async fn invalidate_rp(&self, rp_id: u128) {
+        self.cache
+            .invalidate_entries_if(move |key, _| key.rp_id == rp_id)
+            .expect("closure does not panic");
+    }
+}
+
+/// Redis-backed [`CacheAdapter`], so horizontally-scaled `bitservice-server` instances can share
+/// one cache and one view of rp-scoped invalidation.
+///
+/// Redis has no secondary index to bulk-delete "every key for this rp_id" without `KEYS`/`SCAN`,
+/// so entries are namespaced under a per-rp epoch instead (`bitservice:read:{rp_id}:{epoch}:...`):
+/// [`invalidate_rp`](CacheAdapter::invalidate_rp) just bumps the epoch, which makes every
+/// previously-stored key unreachable, and lets their own TTL reap them in the background.
+pub(crate) struct RedisCache {
+    client: redis::Client,
+}
+
+impl RedisCache {
+    pub(crate) fn new(redis_url: &str) -> eyre::Result<Self> {
+        Ok(Self {
+            client: redis::Client::open(redis_url)?,
+        })
+    }
+
+    fn epoch_key(rp_id: u128) -> String {
+        format!("bitservice:read-epoch:{rp_id}")
+    }
+
+    async fn entry_key(&self, key: &CacheKey) -> eyre::Result<String> {
+        let mut conn = self.client.get_multiplexed_async_connection().await?;
+        let epoch: u64 = conn.get(Self::epoch_key(key.rp_id)).await.unwrap_or(0);
+        Ok(format!(
+            "bitservice:read:{}:{epoch}:{}:{}",
+            key.rp_id,
+            key.sealed_keys.join(":"),
+            STANDARD.encode(&key.root),
+        ))
+    }
+}
+
+impl CacheAdapter for RedisCache {
+    async fn get(&self, key: &CacheKey) -> Option<ReadResponse> {
+        let entry_key = self.entry_key(key).await.ok()?;
+        let mut conn = self
+            .client
+            .get_multiplexed_async_connection()
+            .await
+            .ok()?;
+        let bytes: Option<Vec<u8>> = conn.get(entry_key).await.ok()?;
+        bincode::serde::decode_from_slice(&bytes?, bincode::config::standard())
+            .ok()
+            .map(|(value, _)| value)
+    }
+
+    async fn put(&self, key: CacheKey, value: ReadResponse, ttl: Duration) {
+        let Ok(entry_key) = self.entry_key(&key).await else {
+            return;
+        };
+        let Ok(mut conn) = self.client.get_multiplexed_async_connection().await else {
+            return;
+        };
+        let Ok(bytes) = bincode::serde::encode_to_vec(value, bincode::config::standard()) else {
+            return;
+        };
+        let _: redis::RedisResult<()> = conn.set_ex(entry_key, bytes, ttl.as_secs().max(1)).await;
+    }
+
+    async fn invalidate_rp(&self, rp_id: u128) {
+        let Ok(mut conn) = self.client.get_multiplexed_async_connection().await else {
+            return;
+        };
+        let _: redis::RedisResult<()> = conn.incr(Self::epoch_key(rp_id), 1).await;
+    }
+}
+
+/// Either [`CacheAdapter`] impl, selected by [`crate::config::CacheKind`].
+pub(crate) enum Cache {
+    InMemory(InMemoryCache),
+    Redis(RedisCache),
+}
+
+impl CacheAdapter for Cache {
+    async fn get(&self, key: &CacheKey) -> Option<ReadResponse> {
+        match self {
+            Cache::InMemory(cache) => cache.get(key).await,
+            Cache::Redis(cache) => cache.get(key).await,
+        }
+    }
+
+    async fn put(&self, key: CacheKey, value: ReadResponse, ttl: Duration) {
+        match self {
+            Cache::InMemory(cache) => cache.put(key, value, ttl).await,
+            Cache::Redis(cache) => cache.put(key, value, ttl).await,
+        }
+    }
+
+    async fn invalidate_rp(&self, rp_id: u128) {
+        match self {
+            Cache::InMemory(cache) => cache.invalidate_rp(rp_id).await,
+            Cache::Redis(cache) => cache.invalidate_rp(rp_id).await,
+        }
+    }
+}
+
+pub(crate) type SharedCache = Arc<Cache>;
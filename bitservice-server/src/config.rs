@@ -1,6 +1,7 @@
 use std::{collections::HashMap, net::SocketAddr, path::PathBuf, time::Duration};
 
 use clap::{Parser, ValueEnum};
+use secrecy::SecretString;
 use serde::{Deserialize, Serialize};
 
 /// The environment the service is running in.
@@ -24,6 +25,39 @@ impl Environment {
     }
 }
 
+/// Which channel secures requests/responses sent to the peers.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum PeerChannelKind {
+    /// Trust the HTTP/TLS layer for peer authentication (the previous, and still default,
+    /// behavior).
+    Plain,
+    /// Seal every request/response body to/from a peer under a mutually-authenticated
+    /// `crypto_box`, keyed by this orchestrator's and that peer's static key. See
+    /// `crate::peer_channel`.
+    Authenticated,
+}
+
+/// Which [`crate::cache::CacheAdapter`] backs the read-through cache in front of `RpRwQueue`.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum CacheKind {
+    /// Embedded LRU+TTL cache local to this instance.
+    InMemory,
+    /// Redis-backed cache, shared across horizontally-scaled instances.
+    Redis,
+}
+
+/// How a peer's v1 API is reached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PeerTransportKind {
+    /// This orchestrator connects out to the peer's HTTP server (the previous, and still
+    /// default, behavior).
+    Forward,
+    /// The peer instead dials this orchestrator's relay endpoint and is served over that
+    /// persistent connection, so the peer needs no inbound port reachable. See `crate::relay`.
+    Reverse,
+}
+
 /// The configuration for the bitservice server.
 ///
 /// It can be configured via environment variables or command line arguments using `clap`.
@@ -54,14 +88,124 @@ pub struct BitserviceServerConfig {
     #[clap(long, env = "BITSERVICE_PRUNE_WRITE_INTERVAL", default_value = "128")]
     pub prune_write_interval: usize,
 
+    /// Max number of attempts (including the first) a request to a peer is retried before
+    /// giving up, for connect/timeout errors and 5xx/429 responses
+    #[clap(long, env = "BITSERVICE_PEER_RETRY_MAX_ATTEMPTS", default_value = "3")]
+    pub peer_retry_max_attempts: usize,
+
+    /// Delay before the first retry to a peer; doubles on each subsequent retry up to
+    /// `peer_retry_backoff_max`
+    #[clap(
+        long,
+        env = "BITSERVICE_PEER_RETRY_BACKOFF_BASE",
+        default_value = "50ms",
+        value_parser = humantime::parse_duration
+    )]
+    pub peer_retry_backoff_base: Duration,
+
+    /// Upper bound the exponential retry backoff is capped at, before jitter is applied
+    #[clap(
+        long,
+        env = "BITSERVICE_PEER_RETRY_BACKOFF_MAX",
+        default_value = "2s",
+        value_parser = humantime::parse_duration
+    )]
+    pub peer_retry_backoff_max: Duration,
+
+    /// Consecutive request failures after which a peer's circuit breaker trips open and starts
+    /// failing requests to it fast instead of retrying
+    #[clap(
+        long,
+        env = "BITSERVICE_PEER_CIRCUIT_BREAKER_THRESHOLD",
+        default_value = "5"
+    )]
+    pub peer_circuit_breaker_threshold: u32,
+
+    /// How long a tripped circuit breaker stays open before it admits a single probe request to
+    /// test whether the peer has recovered
+    #[clap(
+        long,
+        env = "BITSERVICE_PEER_CIRCUIT_BREAKER_COOLDOWN",
+        default_value = "10s",
+        value_parser = humantime::parse_duration
+    )]
+    pub peer_circuit_breaker_cooldown: Duration,
+
     /// The max amount of read tasks who are not yet joined
     ///
     /// This limit only exists to limit the amount of JoinHandles in memory if we encounter many reads without a write
     #[clap(long, env = "BITSERVICE_MAX_NUM_READ_TASKS", default_value = "4096")]
     pub max_num_read_tasks: usize,
+
+    /// Which cache backs the read-through cache in front of each rp's read path
+    #[clap(long, env = "BITSERVICE_CACHE_KIND", default_value = "in-memory")]
+    pub cache_kind: CacheKind,
+
+    /// How long a cached read response stays valid before it's re-fetched from the peers
+    #[clap(
+        long,
+        env = "BITSERVICE_CACHE_TTL",
+        default_value = "30s",
+        value_parser = humantime::parse_duration
+    )]
+    pub cache_ttl: Duration,
+
+    /// Max number of entries held by the in-memory cache before LRU eviction kicks in
+    ///
+    /// Only used when `cache_kind` is `in-memory`.
+    #[clap(long, env = "BITSERVICE_CACHE_MAX_CAPACITY", default_value = "100000")]
+    pub cache_max_capacity: u64,
+
+    /// Connection URL of the Redis instance backing the cache
+    ///
+    /// Only used, and required, when `cache_kind` is `redis`.
+    #[clap(long, env = "BITSERVICE_CACHE_REDIS_URL")]
+    pub cache_redis_url: Option<String>,
+
+    /// Which channel secures `read`/`ban`/`unban`/`prune` requests sent to the peers
+    #[clap(
+        long,
+        env = "BITSERVICE_PEER_CHANNEL_KIND",
+        default_value = "plain"
+    )]
+    pub peer_channel_kind: PeerChannelKind,
+
+    /// This orchestrator's secret key for the peer channel. Required when `peer_channel_kind`
+    /// is `authenticated`
+    #[clap(long, env = "BITSERVICE_PEER_CHANNEL_IDENTITY_KEY_PATH")]
+    pub peer_channel_identity_key_path: Option<PathBuf>,
+
+    /// Shared secret a peer must present when dialing this orchestrator's `/relay/{peer_id}`
+    /// endpoint (see `crate::relay`). Required when any configured peer's `transport_kind` is
+    /// `reverse`.
+    #[clap(long, env = "BITSERVICE_RELAY_SHARED_SECRET")]
+    pub relay_shared_secret: Option<SecretString>,
+}
+
+/// One peer's address and, when `peer_channel_kind` is `authenticated`, the path to its peer
+/// channel public key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeerEndpoint {
+    /// How this peer is reached. Defaults to `forward` so existing configs keep working
+    /// unchanged.
+    #[serde(default)]
+    pub transport_kind: PeerTransportKind,
+    /// This peer's HTTP URL. Required when `transport_kind` is `forward`.
+    pub url: Option<String>,
+    /// The id this peer presents when it dials this orchestrator's `/relay/{peer_id}` endpoint
+    /// (see `crate::relay`). Required when `transport_kind` is `reverse`.
+    pub peer_id: Option<String>,
+    /// Required when `peer_channel_kind` is `authenticated`.
+    pub public_key_path: Option<PathBuf>,
+}
+
+impl Default for PeerTransportKind {
+    fn default() -> Self {
+        Self::Forward
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RpBitservicePeersConfig {
-    pub rp_bitservice_peers: HashMap<String, [String; 3]>,
+    pub rp_bitservice_peers: HashMap<String, [PeerEndpoint; 3]>,
 }
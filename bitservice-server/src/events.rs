@@ -0,0 +1,61 @@
+//! Per-rp fan-out of root/ban events, so subscribers (e.g. indexers) can react to state changes
+//! in real time instead of polling the read API. See `api::v1`'s `/subscribe/{rp_id}` endpoint.
+
+use ark_bn254::Fr;
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+/// How many events a subscriber can fall behind before older ones are dropped for it. Chosen to
+/// comfortably outrun `prune_write_interval`-sized bursts of writes without holding much memory.
+const EVENT_CHANNEL_CAPACITY: usize = 1024;
+
+/// One state-change notification for an rp, published by [`crate::rw_queue::RpRwQueue`] as it
+/// commits writes.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type")]
+pub(crate) enum Event {
+    /// A ban or unban committed a new root.
+    RootAdvanced {
+        #[serde(serialize_with = "ark_serde_compat::serialize_bn254_fr")]
+        old_root: Fr,
+        #[serde(serialize_with = "ark_serde_compat::serialize_bn254_fr")]
+        new_root: Fr,
+    },
+    /// A key was banned.
+    Banned {
+        #[serde(serialize_with = "ark_serde_compat::serialize_bn254_fr")]
+        commitment_key: Fr,
+    },
+}
+
+/// Fans [`Event`]s for one rp out to every subscriber of its `/subscribe` websocket.
+///
+/// Backed by a [`broadcast`] channel: a subscriber that falls too far behind doesn't stall
+/// publishing (the single-writer `RpRwQueue` task) - it just gets a `Lagged` notification on its
+/// next receive and jumps forward, which is the channel's built-in behavior.
+#[derive(Clone)]
+pub(crate) struct EventBus {
+    tx: broadcast::Sender<Event>,
+}
+
+impl EventBus {
+    pub(crate) fn new() -> Self {
+        let (tx, _rx) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        Self { tx }
+    }
+
+    pub(crate) fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.tx.subscribe()
+    }
+
+    /// Publishes `event` to every current subscriber; a no-op if nobody's subscribed.
+    pub(crate) fn publish(&self, event: Event) {
+        let _ = self.tx.send(event);
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,20 +1,32 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc};
 
 use axum::serve::ListenerExt;
+use eyre::ContextCompat as _;
+use metrics_exporter_prometheus::PrometheusHandle;
 
 use crate::{
-    config::{BitserviceServerConfig, RpBitservicePeersConfig},
-    rw_queue::RpRwQueue,
+    cache::{Cache, InMemoryCache, RedisCache},
+    config::{BitserviceServerConfig, CacheKind, PeerEndpoint, PeerTransportKind, RpBitservicePeersConfig},
+    events::EventBus,
+    relay::RelayHub,
+    rw_queue::{PeerAddress, PeerRetryConfig, RpRwQueue},
 };
 
 pub(crate) mod api;
+pub(crate) mod cache;
 pub mod config;
+pub(crate) mod events;
 pub mod metrics;
+pub(crate) mod peer_channel;
+pub(crate) mod relay;
 pub(crate) mod rw_queue;
 
 #[derive(Clone)]
 pub(crate) struct RpBitService {
     pub(crate) rw_queue: RpRwQueue,
+    /// Fans out `RootAdvanced`/`Banned` events as `rw_queue` commits writes; see
+    /// `api::v1`'s `/subscribe/{rp_id}` endpoint.
+    pub(crate) events: EventBus,
 }
 
 /// Main application state for the bitservice-server used for Axum.
@@ -24,9 +36,27 @@ pub(crate) struct RpBitService {
 #[derive(Clone)]
 pub(crate) struct AppState {
     pub(crate) rp_bitservices: HashMap<u128, RpBitService>,
+    /// Dial-in registry for peers configured with `transport_kind: reverse`; see `api::relay`.
+    pub(crate) relay_hub: RelayHub,
 }
 
-pub async fn start(config: BitserviceServerConfig) -> eyre::Result<()> {
+/// Resolves one [`PeerEndpoint`] into how `RpRwQueue` should reach it, validating the field its
+/// `transport_kind` requires is actually present.
+fn peer_address(endpoint: &PeerEndpoint) -> eyre::Result<PeerAddress> {
+    match endpoint.transport_kind {
+        PeerTransportKind::Forward => Ok(PeerAddress::Forward(endpoint.url.clone().ok_or_else(
+            || eyre::eyre!("url is required when a peer's transport_kind is forward"),
+        )?)),
+        PeerTransportKind::Reverse => Ok(PeerAddress::Reverse(
+            endpoint
+                .peer_id
+                .clone()
+                .ok_or_else(|| eyre::eyre!("peer_id is required when a peer's transport_kind is reverse"))?,
+        )),
+    }
+}
+
+pub async fn start(config: BitserviceServerConfig, metrics_handle: PrometheusHandle) -> eyre::Result<()> {
     tracing::info!("starting bitservice-server with config: {config:#?}");
 
     let rp_bitservice_peers_config = toml::from_slice::<RpBitservicePeersConfig>(&std::fs::read(
@@ -37,26 +67,92 @@ pub async fn start(config: BitserviceServerConfig) -> eyre::Result<()> {
         .rp_bitservice_peers
         .into_iter()
         .map(|(rp_id, bitservice_peers)| Ok((rp_id.parse()?, bitservice_peers)))
-        .collect::<eyre::Result<HashMap<u128, [String; 3]>>>()?;
+        .collect::<eyre::Result<HashMap<u128, [PeerEndpoint; 3]>>>()?;
+    eyre::ensure!(
+        !rp_bitservice_peers.is_empty(),
+        "rp_bitservice_peers_config must configure at least one rp_id committee"
+    );
+
+    let peer_channel_identity_key = peer_channel::load_identity_key(
+        config.peer_channel_kind,
+        config.peer_channel_identity_key_path.clone(),
+    )?;
+
+    let cache = Arc::new(match config.cache_kind {
+        CacheKind::InMemory => Cache::InMemory(InMemoryCache::new(
+            config.cache_max_capacity,
+            config.cache_ttl,
+        )),
+        CacheKind::Redis => {
+            let redis_url = config
+                .cache_redis_url
+                .context("cache_redis_url is required when cache_kind is redis")?;
+            Cache::Redis(RedisCache::new(&redis_url)?)
+        }
+    });
+
+    let retry_config = PeerRetryConfig {
+        max_attempts: config.peer_retry_max_attempts,
+        backoff_base: config.peer_retry_backoff_base,
+        backoff_max: config.peer_retry_backoff_max,
+        circuit_breaker_threshold: config.peer_circuit_breaker_threshold,
+        circuit_breaker_cooldown: config.peer_circuit_breaker_cooldown,
+    };
+
+    let uses_reverse_transport = rp_bitservice_peers
+        .values()
+        .flatten()
+        .any(|endpoint| matches!(endpoint.transport_kind, PeerTransportKind::Reverse));
+    eyre::ensure!(
+        !uses_reverse_transport || config.relay_shared_secret.is_some(),
+        "relay_shared_secret is required when any peer's transport_kind is reverse"
+    );
+    let relay_hub = RelayHub::new(config.relay_shared_secret.clone());
+
     let rp_bitservices = rp_bitservice_peers
         .into_iter()
-        .map(|(rp_id, peers)| {
-            (
+        .map(|(rp_id, endpoints)| {
+            let peer_channels =
+                peer_channel::build_peer_channels(peer_channel_identity_key.as_ref(), &endpoints)?;
+            let peers = [
+                peer_address(&endpoints[0])?,
+                peer_address(&endpoints[1])?,
+                peer_address(&endpoints[2])?,
+            ];
+            let events = EventBus::new();
+            Ok((
                 rp_id,
                 RpBitService {
                     rw_queue: RpRwQueue::new(
+                        rp_id,
                         peers,
+                        relay_hub.clone(),
                         config.prune_write_interval,
                         config.max_num_read_tasks,
                         config.peer_request_timeout,
+                        Arc::clone(&cache),
+                        config.cache_ttl,
+                        events.clone(),
+                        retry_config,
+                        peer_channels,
                     ),
+                    events,
                 },
-            )
+            ))
         })
-        .collect();
+        .collect::<eyre::Result<HashMap<u128, RpBitService>>>()?;
+    metrics::record_configured_committees(rp_bitservices.len());
+    tracing::info!(
+        "routing {} rp_id committee(s): {:?}",
+        rp_bitservices.len(),
+        rp_bitservices.keys().collect::<Vec<_>>()
+    );
 
-    let app_state = AppState { rp_bitservices };
-    let app = api::new_app(app_state);
+    let app_state = AppState {
+        rp_bitservices,
+        relay_hub,
+    };
+    let app = api::new_app(app_state, metrics_handle);
 
     let listener = tokio::net::TcpListener::bind(config.bind_addr)
         .await?
@@ -0,0 +1,101 @@
+//! Prometheus metric definitions for bitservice-server.
+//!
+//! Call [`describe_metrics`] once at startup so every metric has a help string attached before
+//! the first observation, and [`install_recorder`] once to actually register a global recorder -
+//! modeled on pict-rs's use of `metrics_exporter_prometheus`, a handle is returned so the caller
+//! can expose it as a `/metrics` route rather than the exporter running its own listener.
+
+use std::time::Duration;
+
+use metrics::{counter, describe_counter, describe_gauge, describe_histogram, gauge, histogram};
+use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
+
+const READ_CACHE_HITS: &str = "bitservice_server_read_cache_hits_total";
+const READ_CACHE_MISSES: &str = "bitservice_server_read_cache_misses_total";
+const RW_QUEUE_DEPTH: &str = "bitservice_server_rw_queue_depth";
+const READ_TASKS_IN_FLIGHT: &str = "bitservice_server_read_tasks_in_flight";
+const PEER_OP_DURATION_SECONDS: &str = "bitservice_server_peer_op_duration_seconds";
+const PEER_FAILURES: &str = "bitservice_server_peer_failures_total";
+const PRUNE_TRIGGERS: &str = "bitservice_server_prune_triggers_total";
+const CONFIGURED_COMMITTEES: &str = "bitservice_server_configured_committees";
+
+/// Registers descriptions for all metrics emitted by this crate. Call once at startup.
+pub fn describe_metrics() {
+    describe_counter!(
+        READ_CACHE_HITS,
+        "Number of read requests served from the read-through cache"
+    );
+    describe_counter!(
+        READ_CACHE_MISSES,
+        "Number of read requests that missed the read-through cache and were dispatched to the peers"
+    );
+    describe_gauge!(
+        RW_QUEUE_DEPTH,
+        "Number of RpRwQueueMsg currently queued, waiting for RpRwQueue's worker task to pick them up"
+    );
+    describe_gauge!(
+        READ_TASKS_IN_FLIGHT,
+        "Number of read tasks RpRwQueue currently has spawned, out of max_num_read_tasks"
+    );
+    describe_histogram!(
+        PEER_OP_DURATION_SECONDS,
+        "Time spent in a do_peer_* round-trip to all three peers, by operation kind"
+    );
+    describe_counter!(
+        PEER_FAILURES,
+        "Number of post_to_peers calls that failed for a given peer"
+    );
+    describe_counter!(
+        PRUNE_TRIGGERS,
+        "Number of times RpRwQueue triggered a prune after prune_write_interval writes"
+    );
+    describe_gauge!(
+        CONFIGURED_COMMITTEES,
+        "Number of distinct rp_id committees loaded from rp_bitservice_peers_config, each \
+         routed to its own three-peer RpRwQueue"
+    );
+}
+
+/// Builds and installs the global Prometheus recorder, returning a handle whose `render()`
+/// produces the text-format scrape body - see `api::metrics_route`. Must be called once, before
+/// `describe_metrics` and any `record_*` call.
+pub fn install_recorder() -> eyre::Result<PrometheusHandle> {
+    PrometheusBuilder::new()
+        .install_recorder()
+        .map_err(|err| eyre::eyre!("failed to install prometheus recorder: {err}"))
+}
+
+pub(crate) fn record_read_cache_hit(rp_id: u128) {
+    counter!(READ_CACHE_HITS, "rp_id" => rp_id.to_string()).increment(1);
+}
+
+pub(crate) fn record_read_cache_miss(rp_id: u128) {
+    counter!(READ_CACHE_MISSES, "rp_id" => rp_id.to_string()).increment(1);
+}
+
+pub(crate) fn record_rw_queue_depth(rp_id: u128, depth: usize) {
+    gauge!(RW_QUEUE_DEPTH, "rp_id" => rp_id.to_string()).set(depth as f64);
+}
+
+pub(crate) fn record_read_tasks_in_flight(rp_id: u128, count: usize) {
+    gauge!(READ_TASKS_IN_FLIGHT, "rp_id" => rp_id.to_string()).set(count as f64);
+}
+
+pub(crate) fn record_peer_op_duration(rp_id: u128, op: &'static str, duration: Duration) {
+    histogram!(PEER_OP_DURATION_SECONDS, "rp_id" => rp_id.to_string(), "op" => op)
+        .record(duration.as_secs_f64());
+}
+
+pub(crate) fn record_peer_failure(rp_id: u128, peer: &'static str) {
+    counter!(PEER_FAILURES, "rp_id" => rp_id.to_string(), "peer" => peer).increment(1);
+}
+
+pub(crate) fn record_prune_trigger(rp_id: u128) {
+    counter!(PRUNE_TRIGGERS, "rp_id" => rp_id.to_string()).increment(1);
+}
+
+/// Reports how many rp_id committees this instance routes requests across, so an operator can
+/// confirm the expected shards came up (and notice if one silently failed to load).
+pub(crate) fn record_configured_committees(count: usize) {
+    gauge!(CONFIGURED_COMMITTEES).set(count as f64);
+}
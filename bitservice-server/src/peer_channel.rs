@@ -0,0 +1,50 @@
+//! Builds the per-peer [`SalsaBox`]es that seal/unseal `read`/`ban`/`unban`/`prune` bodies when
+//! `peer_channel_kind` is `authenticated`, so only the holder of this orchestrator's identity
+//! key can talk to the peers, and only these configured peers can answer for it. See
+//! [`bitservice_types::peer_channel`] for the wire format and `bitservice_peer::api::peer_channel`
+//! for the matching guard on the peer side.
+
+use crypto_box::SalsaBox;
+
+use crate::config::PeerEndpoint;
+
+/// Loads this orchestrator's peer channel identity key, or returns `None` for the plain,
+/// unauthenticated behavior.
+pub(crate) fn load_identity_key(
+    peer_channel_kind: crate::config::PeerChannelKind,
+    identity_key_path: Option<std::path::PathBuf>,
+) -> eyre::Result<Option<crypto_box::SecretKey>> {
+    match peer_channel_kind {
+        crate::config::PeerChannelKind::Plain => Ok(None),
+        crate::config::PeerChannelKind::Authenticated => {
+            let identity_key_path = identity_key_path.ok_or_else(|| {
+                eyre::eyre!("peer_channel_identity_key_path is required when peer_channel_kind is authenticated")
+            })?;
+            let bytes = std::fs::read(identity_key_path)?;
+            Ok(Some(crypto_box::SecretKey::from_slice(&bytes)?))
+        }
+    }
+}
+
+/// Builds the box for each of `endpoints`' configured public keys, keyed to `identity_key`, or
+/// `None` for every peer when `identity_key` is `None`.
+pub(crate) fn build_peer_channels(
+    identity_key: Option<&crypto_box::SecretKey>,
+    endpoints: &[PeerEndpoint; 3],
+) -> eyre::Result<[Option<SalsaBox>; 3]> {
+    let Some(identity_key) = identity_key else {
+        return Ok([None, None, None]);
+    };
+    let mut channels: [Option<SalsaBox>; 3] = [None, None, None];
+    for (channel, endpoint) in channels.iter_mut().zip(endpoints) {
+        let public_key_path = endpoint.public_key_path.as_ref().ok_or_else(|| {
+            eyre::eyre!(
+                "peer {:?} is missing public_key_path, required when peer_channel_kind is authenticated",
+                endpoint.url.as_deref().or(endpoint.peer_id.as_deref())
+            )
+        })?;
+        let public_key = crypto_box::PublicKey::from_slice(&std::fs::read(public_key_path)?)?;
+        *channel = Some(SalsaBox::new(&public_key, identity_key));
+    }
+    Ok(channels)
+}
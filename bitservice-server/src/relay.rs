@@ -0,0 +1,245 @@
+//! Dial-in registry for peers using the reverse transport (see [`bitservice_types::relay`]):
+//! each peer connects out to `/relay/{peer_id}` and parks its connection here, so `RpRwQueue`
+//! can hand a request off over it instead of posting to a URL it owns. Modeled on
+//! `ws_mpc_net::WsSessions`, keyed by `peer_id` instead of a one-shot session `Uuid`, with a
+//! second table correlating an in-flight request to its response by `request_id`.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use axum::extract::ws::{Message, WebSocket};
+use bitservice_types::relay::{RelayFrame, RelayOp};
+use futures::{SinkExt as _, StreamExt as _};
+use secrecy::{ExposeSecret as _, SecretString};
+use subtle::ConstantTimeEq as _;
+use tokio::sync::{Mutex, mpsc, oneshot};
+use uuid::Uuid;
+
+/// A peer's parked relay connection: [`RelayHub::send_request`] writes to `outbox`, which the
+/// connection's loop in [`RelayHub::serve`] forwards onto the socket.
+struct ParkedPeer {
+    outbox: mpsc::Sender<Message>,
+    /// Identifies which dial-in this entry belongs to, so `serve`'s disconnect cleanup only
+    /// evicts the entry it parked - a reconnect's new connection can land in the map before the
+    /// old connection's socket notices it's dead, and that old connection's cleanup must not
+    /// then evict the new one.
+    generation: u64,
+}
+
+#[derive(Clone)]
+pub(crate) struct RelayHub {
+    peers: Arc<Mutex<HashMap<String, ParkedPeer>>>,
+    pending: Arc<Mutex<HashMap<Uuid, oneshot::Sender<Vec<u8>>>>>,
+    next_generation: Arc<AtomicU64>,
+    /// The secret a peer must present (see [`Self::authorize`]) when dialing `/relay/{peer_id}`.
+    /// Without this, any client could claim any `peer_id` and start receiving that peer's
+    /// `read`/`ban`/`unban`/`prune` traffic. `start` refuses to boot with a reverse-transport
+    /// peer configured and no secret set, so `None` here only ever means no peer can dial in.
+    shared_secret: Option<SecretString>,
+}
+
+impl RelayHub {
+    pub(crate) fn new(shared_secret: Option<SecretString>) -> Self {
+        Self {
+            peers: Arc::default(),
+            pending: Arc::default(),
+            next_generation: Arc::new(AtomicU64::new(0)),
+            shared_secret,
+        }
+    }
+
+    /// Checks a dial-in's presented credential (the `RELAY_SHARED_SECRET_HEADER` value) against
+    /// the configured shared secret. Denies by default, including when no secret is configured.
+    /// Compares in constant time (as `tcp_mpc_net::auth`'s handshake does via `Mac::verify_slice`)
+    /// so a byte-by-byte `==` can't leak the secret through response-timing differences.
+    pub(crate) fn authorize(&self, credential: Option<&str>) -> bool {
+        match (&self.shared_secret, credential) {
+            (Some(secret), Some(credential)) => bool::from(
+                secret
+                    .expose_secret()
+                    .as_bytes()
+                    .ct_eq(credential.as_bytes()),
+            ),
+            _ => false,
+        }
+    }
+
+    /// Parks `socket` under `peer_id`, replacing any previous connection for it, and serves it
+    /// until it closes or errors. Every relay frame received over it is treated as a response and
+    /// routed to the matching entry in `pending` - `peer_id` never sends anything unprompted.
+    pub(crate) async fn serve(&self, peer_id: String, socket: WebSocket) {
+        let generation = self.next_generation.fetch_add(1, Ordering::Relaxed);
+        let (mut sink, mut stream) = socket.split();
+        let (outbox_tx, mut outbox_rx) = mpsc::channel::<Message>(32);
+        self.peers.lock().await.insert(
+            peer_id.clone(),
+            ParkedPeer {
+                outbox: outbox_tx,
+                generation,
+            },
+        );
+        tracing::info!("peer {peer_id} connected to relay");
+
+        loop {
+            tokio::select! {
+                message = outbox_rx.recv() => {
+                    match message {
+                        Some(message) => {
+                            if sink.send(message).await.is_err() {
+                                break;
+                            }
+                        }
+                        None => break,
+                    }
+                }
+                message = stream.next() => {
+                    match message {
+                        Some(Ok(Message::Binary(data))) => self.resolve_response(&data).await,
+                        Some(Ok(_)) => {}
+                        _ => break,
+                    }
+                }
+            }
+        }
+
+        // Only remove the entry if it's still this connection's own - a reconnect may already
+        // have parked a newer one under `peer_id` before this connection noticed its socket was
+        // dead, and evicting that would wrongly fail the now-connected peer's requests.
+        let mut peers = self.peers.lock().await;
+        evict_if_current(&mut peers, &peer_id, generation);
+        drop(peers);
+        tracing::warn!("peer {peer_id} disconnected from relay");
+    }
+
+    /// Resolves a relay frame received from `peer_id`'s connection as the response to whichever
+    /// `send_request` call is still waiting on its `request_id`, if any.
+    async fn resolve_response(&self, data: &[u8]) {
+        let frame: RelayFrame = match serde_json::from_slice(data) {
+            Ok(frame) => frame,
+            Err(err) => {
+                tracing::warn!("dropping malformed relay frame: {err:?}");
+                return;
+            }
+        };
+        if let Some(sender) = self.pending.lock().await.remove(&frame.request_id) {
+            let _ = sender.send(frame.body);
+        }
+    }
+
+    /// Hands `body` off to `peer_id`'s parked connection, tagged with `op` and `request_id`, and
+    /// awaits the matching response - failing if the peer isn't currently connected, the send
+    /// fails, or no response arrives within `timeout`.
+    pub(crate) async fn send_request(
+        &self,
+        peer_id: &str,
+        request_id: Uuid,
+        op: RelayOp,
+        body: Vec<u8>,
+        timeout: Duration,
+    ) -> eyre::Result<Vec<u8>> {
+        let outbox = {
+            let peers = self.peers.lock().await;
+            peers
+                .get(peer_id)
+                .ok_or_else(|| eyre::eyre!("peer {peer_id} is not connected to the relay"))?
+                .outbox
+                .clone()
+        };
+
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().await.insert(request_id, tx);
+
+        let frame = RelayFrame {
+            request_id,
+            op: Some(op),
+            body,
+        };
+        let encoded = serde_json::to_vec(&frame)?;
+        if outbox.send(Message::Binary(encoded.into())).await.is_err() {
+            self.pending.lock().await.remove(&request_id);
+            eyre::bail!("peer {peer_id}'s relay connection closed while sending request");
+        }
+
+        match tokio::time::timeout(timeout, rx).await {
+            Ok(Ok(body)) => Ok(body),
+            Ok(Err(_)) => {
+                eyre::bail!("peer {peer_id}'s relay connection dropped before answering")
+            }
+            Err(_) => {
+                self.pending.lock().await.remove(&request_id);
+                eyre::bail!("timed out waiting for peer {peer_id} to answer over the relay")
+            }
+        }
+    }
+}
+
+/// Removes `peer_id`'s entry from `peers` only if it's still the one tagged with `generation` -
+/// pulled out of [`RelayHub::serve`]'s disconnect cleanup so the reconnect race it guards against
+/// (a benign redial parking a newer connection before the old one's socket notices it's dead) can
+/// be exercised without a real websocket.
+fn evict_if_current(peers: &mut HashMap<String, ParkedPeer>, peer_id: &str, generation: u64) {
+    if peers
+        .get(peer_id)
+        .is_some_and(|parked| parked.generation == generation)
+    {
+        peers.remove(peer_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::mpsc;
+
+    use super::*;
+
+    fn parked_peer(generation: u64) -> ParkedPeer {
+        let (outbox, _rx) = mpsc::channel(1);
+        ParkedPeer { outbox, generation }
+    }
+
+    #[test]
+    fn stale_disconnect_does_not_evict_a_newer_reconnect() {
+        let mut peers = HashMap::new();
+        peers.insert("peer-a".to_string(), parked_peer(1));
+
+        // Peer reconnects before the old connection's socket notices it's dead: a newer
+        // generation is parked under the same peer_id.
+        peers.insert("peer-a".to_string(), parked_peer(2));
+
+        // The old connection's cleanup runs with its own, now-stale generation.
+        evict_if_current(&mut peers, "peer-a", 1);
+
+        assert_eq!(peers.get("peer-a").map(|parked| parked.generation), Some(2));
+    }
+
+    #[test]
+    fn matching_disconnect_evicts_its_own_entry() {
+        let mut peers = HashMap::new();
+        peers.insert("peer-a".to_string(), parked_peer(1));
+
+        evict_if_current(&mut peers, "peer-a", 1);
+
+        assert!(!peers.contains_key("peer-a"));
+    }
+
+    #[test]
+    fn authorize_denies_without_a_configured_secret() {
+        let hub = RelayHub::new(None);
+        assert!(!hub.authorize(Some("anything")));
+        assert!(!hub.authorize(None));
+    }
+
+    #[test]
+    fn authorize_requires_a_matching_credential() {
+        let hub = RelayHub::new(Some(SecretString::from("s3cret".to_string())));
+        assert!(!hub.authorize(None));
+        assert!(!hub.authorize(Some("wrong")));
+        assert!(hub.authorize(Some("s3cret")));
+    }
+}
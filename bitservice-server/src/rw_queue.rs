@@ -1,20 +1,37 @@
-use std::{sync::Arc, time::Duration};
+use std::{
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::{Duration, Instant},
+};
 
 use bitservice_types::{
     ban::{BanRequest, BanResponse},
+    peer_channel::SealedEnvelope,
     prune::{PeerPruneRequest, PeerPruneResponse},
     read::{ReadRequest, ReadResponse},
+    relay::RelayOp,
     unban::{UnbanRequest, UnbanResponse},
 };
+use crypto_box::SalsaBox;
 use eyre::Context as _;
+use rand::Rng as _;
 use reqwest::IntoUrl;
 use serde::{Serialize, de::DeserializeOwned};
 use tokio::{
-    sync::{mpsc, oneshot},
+    sync::{RwLock, mpsc, oneshot},
     task::JoinSet,
 };
 use uuid::Uuid;
 
+use crate::{
+    cache::{CacheAdapter, CacheKey, SharedCache},
+    events::{Event, EventBus},
+    metrics,
+    relay::RelayHub,
+};
+
 pub(crate) struct ReadMsg {
     pub(crate) req: ReadRequest,
     pub(crate) request_id: Uuid,
@@ -43,19 +60,136 @@ pub(crate) enum RpRwQueueMsg {
     Write(Box<WriteMsg>),
 }
 
+/// Retry/backoff/circuit-breaker tuning for requests `post_to_peers` sends to a peer.
+///
+/// Mirrors `ReconnectConfig` in `ws-mpc-net`: a request is retried with exponential backoff up
+/// to `max_attempts` times, only for transport-level connect/timeout errors and 5xx/429
+/// responses (never for other 4xx, which are taken as the peer rejecting the request outright).
+/// Each peer additionally gets its own [`CircuitBreaker`] so a peer that's down doesn't eat
+/// `max_attempts * backoff` on every single request.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PeerRetryConfig {
+    /// Number of attempts (including the first) before a request is given up on.
+    pub(crate) max_attempts: usize,
+    /// Delay before the first retry.
+    pub(crate) backoff_base: Duration,
+    /// Upper bound the exponential backoff is capped at, before jitter is applied.
+    pub(crate) backoff_max: Duration,
+    /// Consecutive failed attempts (each failed retry counts) after which a peer's breaker trips
+    /// to `Open`.
+    pub(crate) circuit_breaker_threshold: u32,
+    /// How long a tripped breaker stays `Open` before admitting a single `HalfOpen` probe.
+    pub(crate) circuit_breaker_cooldown: Duration,
+}
+
+/// Per-peer circuit breaker state: `Closed` (requests flow normally) -> `Open` (after
+/// `circuit_breaker_threshold` consecutive failures, requests fail fast) -> `HalfOpen` (after
+/// `circuit_breaker_cooldown`, exactly one probe is admitted to test recovery).
+#[derive(Debug, Clone, Copy)]
+enum CircuitBreakerState {
+    Closed { consecutive_failures: u32 },
+    Open { opened_at: Instant },
+    HalfOpen,
+}
+
+struct CircuitBreaker {
+    state: Mutex<CircuitBreakerState>,
+}
+
+impl CircuitBreaker {
+    fn new() -> Self {
+        Self {
+            state: Mutex::new(CircuitBreakerState::Closed {
+                consecutive_failures: 0,
+            }),
+        }
+    }
+
+    /// Returns whether a request may proceed. The call that observes a tripped breaker's
+    /// cooldown has elapsed is the one that flips it to `HalfOpen` and is admitted as the probe;
+    /// any other concurrent caller sees `HalfOpen` and is turned away until the probe resolves.
+    fn admit(&self, cooldown: Duration) -> bool {
+        let mut state = self.state.lock().expect("circuit breaker mutex poisoned");
+        match *state {
+            CircuitBreakerState::Closed { .. } => true,
+            CircuitBreakerState::Open { opened_at } => {
+                if opened_at.elapsed() >= cooldown {
+                    *state = CircuitBreakerState::HalfOpen;
+                    true
+                } else {
+                    false
+                }
+            }
+            CircuitBreakerState::HalfOpen => false,
+        }
+    }
+
+    fn record_success(&self) {
+        let mut state = self.state.lock().expect("circuit breaker mutex poisoned");
+        *state = CircuitBreakerState::Closed {
+            consecutive_failures: 0,
+        };
+    }
+
+    fn record_failure(&self, threshold: u32) {
+        let mut state = self.state.lock().expect("circuit breaker mutex poisoned");
+        *state = match *state {
+            CircuitBreakerState::Closed {
+                consecutive_failures,
+            } if consecutive_failures + 1 >= threshold => CircuitBreakerState::Open {
+                opened_at: Instant::now(),
+            },
+            CircuitBreakerState::Closed {
+                consecutive_failures,
+            } => CircuitBreakerState::Closed {
+                consecutive_failures: consecutive_failures + 1,
+            },
+            // The admitted probe failed: back to Open for another full cooldown.
+            CircuitBreakerState::HalfOpen => CircuitBreakerState::Open {
+                opened_at: Instant::now(),
+            },
+            open @ CircuitBreakerState::Open { .. } => open,
+        };
+    }
+}
+
+/// How `RpRwQueue` reaches one of its three peers - see [`crate::relay`] for the reverse transport.
+#[derive(Debug, Clone)]
+pub(crate) enum PeerAddress {
+    /// The peer's HTTP URL; requests are POSTed to it directly.
+    Forward(String),
+    /// The id under which the peer is parked in a [`RelayHub`] after dialing in itself. See
+    /// `bitservice_types::relay`.
+    Reverse(String),
+}
+
 #[derive(Clone)]
 pub(crate) struct RpRwQueue {
+    rp_id: u128,
     queue: mpsc::Sender<RpRwQueueMsg>,
+    /// Number of messages sent but not yet picked up by the worker loop, reported via
+    /// `metrics::record_rw_queue_depth` so an operator can alert on the read/write queue
+    /// saturating.
+    queue_depth: Arc<AtomicUsize>,
 }
 
 impl RpRwQueue {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
-        peers: [String; 3],
+        rp_id: u128,
+        peers: [PeerAddress; 3],
+        relay_hub: RelayHub,
         prune_write_interval: usize,
         max_num_read_tasks: usize,
         request_timeout: Duration,
+        cache: SharedCache,
+        cache_ttl: Duration,
+        events: EventBus,
+        retry_config: PeerRetryConfig,
+        peer_channels: [Option<SalsaBox>; 3],
     ) -> Self {
         let (tx, mut rx) = mpsc::channel(32); // TODO or unbounded?
+        let queue_depth = Arc::new(AtomicUsize::new(0));
         let mut read_tasks = JoinSet::new();
         let client = reqwest::Client::builder()
             .timeout(request_timeout)
@@ -63,10 +197,27 @@ impl RpRwQueue {
             .expect("can build client");
         let mut prune_write_counter = 0;
         let peers = Arc::new(peers);
+        let peer_channels = Arc::new(peer_channels);
+        let breakers = Arc::new([
+            CircuitBreaker::new(),
+            CircuitBreaker::new(),
+            CircuitBreaker::new(),
+        ]);
+        // The root this rp's reads are currently cached under. `None` until the first read or
+        // write tells us what it is, at which point reads start getting cached; see `cache.rs`.
+        let current_root = Arc::new(RwLock::new(None));
+        let queue_depth_task = Arc::clone(&queue_depth);
         tokio::spawn(async move {
             while let Some(msg) = rx.recv().await {
+                metrics::record_rw_queue_depth(
+                    rp_id,
+                    queue_depth_task.fetch_sub(1, Ordering::Relaxed) - 1,
+                );
                 let client = client.clone();
+                let relay_hub = relay_hub.clone();
                 let peers = Arc::clone(&peers);
+                let peer_channels = Arc::clone(&peer_channels);
+                let breakers = Arc::clone(&breakers);
                 match msg {
                     RpRwQueueMsg::Read(read) => {
                         let ReadMsg {
@@ -83,10 +234,48 @@ impl RpRwQueue {
                             // on the next write, all read tasks are cleared
                             read_tasks.join_next().await;
                         }
+                        metrics::record_read_tasks_in_flight(rp_id, read_tasks.len());
+                        let cache = Arc::clone(&cache);
+                        let current_root = Arc::clone(&current_root);
                         read_tasks.spawn(async move {
-                            match do_peer_read(client, &peers, req, request_id).await {
+                            let sealed_keys = req.requests.clone().map(|peer_req| peer_req.key);
+                            let root_snapshot = *current_root.read().await;
+                            let cache_key = root_snapshot
+                                .map(|root| CacheKey::new(rp_id, sealed_keys.clone(), root));
+                            if let Some(cache_key) = &cache_key {
+                                if let Some(cached) = cache.get(cache_key).await {
+                                    tracing::debug!("read request {request_id} served from cache");
+                                    metrics::record_read_cache_hit(rp_id);
+                                    let _ = sender.send(Ok(cached));
+                                    return;
+                                }
+                            }
+                            metrics::record_read_cache_miss(rp_id);
+                            let start = Instant::now();
+                            let result = do_peer_read(
+                                rp_id,
+                                client,
+                                relay_hub,
+                                &peers,
+                                &peer_channels,
+                                &breakers,
+                                retry_config,
+                                request_timeout,
+                                req,
+                                request_id,
+                            )
+                            .await;
+                            metrics::record_peer_op_duration(rp_id, "read", start.elapsed());
+                            match result {
                                 Ok(res) => {
                                     tracing::debug!("read request {request_id} done");
+                                    if let Some(root) =
+                                        res.responses.first().map(|response| response.root)
+                                    {
+                                        *current_root.write().await = Some(root);
+                                        let cache_key = CacheKey::new(rp_id, sealed_keys, root);
+                                        cache.put(cache_key, res.clone(), cache_ttl).await;
+                                    }
                                     let _ = sender.send(Ok(res));
                                 }
                                 Err(err) => {
@@ -104,6 +293,7 @@ impl RpRwQueue {
                         tracing::debug!("waiting for {} read tasks to be done", reads.len());
                         reads.join_all().await;
                         tracing::debug!("all read tasks are done");
+                        metrics::record_read_tasks_in_flight(rp_id, 0);
                         match *write_msg {
                             WriteMsg::Ban(BanMsg {
                                 req,
@@ -111,9 +301,35 @@ impl RpRwQueue {
                                 sender,
                             }) => {
                                 tracing::debug!("got ban request {request_id}");
-                                match do_peer_ban(client.clone(), &peers, req, request_id).await {
+                                let start = Instant::now();
+                                let result = do_peer_ban(
+                                    rp_id,
+                                    client.clone(),
+                                    relay_hub.clone(),
+                                    &peers,
+                                    &peer_channels,
+                                    &breakers,
+                                    retry_config,
+                                    request_timeout,
+                                    req,
+                                    request_id,
+                                )
+                                .await;
+                                metrics::record_peer_op_duration(rp_id, "ban", start.elapsed());
+                                match result {
                                     Ok(res) => {
                                         tracing::debug!("ban request {request_id} done");
+                                        if let Some(response) = res.responses.first() {
+                                            *current_root.write().await = Some(response.new_root);
+                                            events.publish(Event::RootAdvanced {
+                                                old_root: response.old_root,
+                                                new_root: response.new_root,
+                                            });
+                                            events.publish(Event::Banned {
+                                                commitment_key: response.commitment_key,
+                                            });
+                                        }
+                                        cache.invalidate_rp(rp_id).await;
                                         let _ = sender.send(Ok(res));
                                     }
                                     Err(err) => {
@@ -130,9 +346,32 @@ impl RpRwQueue {
                                 sender,
                             }) => {
                                 tracing::debug!("got unban request {request_id}");
-                                match do_peer_unban(client.clone(), &peers, req, request_id).await {
+                                let start = Instant::now();
+                                let result = do_peer_unban(
+                                    rp_id,
+                                    client.clone(),
+                                    relay_hub.clone(),
+                                    &peers,
+                                    &peer_channels,
+                                    &breakers,
+                                    retry_config,
+                                    request_timeout,
+                                    req,
+                                    request_id,
+                                )
+                                .await;
+                                metrics::record_peer_op_duration(rp_id, "unban", start.elapsed());
+                                match result {
                                     Ok(res) => {
                                         tracing::debug!("unban request {request_id} done");
+                                        if let Some(response) = res.responses.first() {
+                                            *current_root.write().await = Some(response.new_root);
+                                            events.publish(Event::RootAdvanced {
+                                                old_root: response.old_root,
+                                                new_root: response.new_root,
+                                            });
+                                        }
+                                        cache.invalidate_rp(rp_id).await;
                                         let _ = sender.send(Ok(res));
                                     }
                                     Err(err) => {
@@ -150,10 +389,28 @@ impl RpRwQueue {
                             tracing::debug!(
                                 "reached prune_write_interval {prune_write_interval} - send prune request"
                             );
+                            metrics::record_prune_trigger(rp_id);
                             let request_id = Uuid::new_v4();
-                            match do_peer_prune(client, &peers, request_id).await {
+                            let start = Instant::now();
+                            let result = do_peer_prune(
+                                rp_id,
+                                client,
+                                relay_hub,
+                                &peers,
+                                &peer_channels,
+                                &breakers,
+                                retry_config,
+                                request_timeout,
+                                request_id,
+                            )
+                            .await;
+                            metrics::record_peer_op_duration(rp_id, "prune", start.elapsed());
+                            match result {
                                 Ok(_) => {
                                     tracing::debug!("prune request {request_id} done");
+                                    // prune doesn't hand back a new root to key future reads
+                                    // under, so drop everything cached for this rp instead.
+                                    cache.invalidate_rp(rp_id).await;
                                 }
                                 Err(err) => {
                                     tracing::warn!(
@@ -167,12 +424,25 @@ impl RpRwQueue {
                 }
             }
         });
-        Self { queue: tx }
+        Self {
+            rp_id,
+            queue: tx,
+            queue_depth,
+        }
+    }
+
+    /// Records the queued message in `queue_depth` before handing it to the worker loop, so
+    /// `metrics::record_rw_queue_depth` reflects how many are waiting even before any of them
+    /// are picked up.
+    fn record_enqueued(&self) {
+        let depth = self.queue_depth.fetch_add(1, Ordering::Relaxed) + 1;
+        metrics::record_rw_queue_depth(self.rp_id, depth);
     }
 
     pub(crate) async fn read(&self, req: ReadRequest) -> eyre::Result<ReadResponse> {
         let request_id = Uuid::new_v4();
         let (tx, rx) = oneshot::channel();
+        self.record_enqueued();
         self.queue
             .send(RpRwQueueMsg::Read(
                 ReadMsg {
@@ -189,6 +459,7 @@ impl RpRwQueue {
     pub(crate) async fn ban(&self, req: BanRequest) -> eyre::Result<BanResponse> {
         let request_id = Uuid::new_v4();
         let (tx, rx) = oneshot::channel();
+        self.record_enqueued();
         self.queue
             .send(RpRwQueueMsg::Write(
                 WriteMsg::Ban(BanMsg {
@@ -205,6 +476,7 @@ impl RpRwQueue {
     pub(crate) async fn unban(&self, req: UnbanRequest) -> eyre::Result<UnbanResponse> {
         let request_id = Uuid::new_v4();
         let (tx, rx) = oneshot::channel();
+        self.record_enqueued();
         self.queue
             .send(RpRwQueueMsg::Write(
                 WriteMsg::Unban(UnbanMsg {
@@ -219,96 +491,334 @@ impl RpRwQueue {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn do_peer_read(
+    rp_id: u128,
     client: reqwest::Client,
-    peers: &[String; 3],
+    relay_hub: RelayHub,
+    peers: &[PeerAddress; 3],
+    peer_channels: &[Option<SalsaBox>; 3],
+    breakers: &[CircuitBreaker; 3],
+    retry_config: PeerRetryConfig,
+    relay_timeout: Duration,
     req: ReadRequest,
     request_id: Uuid,
 ) -> eyre::Result<ReadResponse> {
     tracing::debug!("send read request {request_id} to peers {peers:?}");
-    let urls = peers
-        .clone()
-        .map(|peer| format!("{peer}/api/v1/read/{request_id}"));
-    let responses = post_to_peers(client, urls, &req.requests).await?;
+    let responses = post_to_peers(
+        rp_id,
+        client,
+        relay_hub,
+        peers,
+        RelayOp::Read,
+        peer_channels,
+        breakers,
+        retry_config,
+        relay_timeout,
+        &req.requests,
+        request_id,
+    )
+    .await?;
     tracing::debug!("got read response for request {request_id}");
     Ok(ReadResponse { responses })
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn do_peer_ban(
+    rp_id: u128,
     client: reqwest::Client,
-    peers: &[String; 3],
+    relay_hub: RelayHub,
+    peers: &[PeerAddress; 3],
+    peer_channels: &[Option<SalsaBox>; 3],
+    breakers: &[CircuitBreaker; 3],
+    retry_config: PeerRetryConfig,
+    relay_timeout: Duration,
     req: BanRequest,
     request_id: Uuid,
 ) -> eyre::Result<BanResponse> {
     tracing::debug!("send ban request {request_id} to peers {peers:?}");
-    let urls = peers
-        .clone()
-        .map(|peer| format!("{peer}/api/v1/ban/{request_id}"));
-    let responses = post_to_peers(client, urls, &req.requests).await?;
+    let responses = post_to_peers(
+        rp_id,
+        client,
+        relay_hub,
+        peers,
+        RelayOp::Ban,
+        peer_channels,
+        breakers,
+        retry_config,
+        relay_timeout,
+        &req.requests,
+        request_id,
+    )
+    .await?;
     tracing::debug!("got ban response for request {request_id}");
     Ok(BanResponse { responses })
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn do_peer_unban(
+    rp_id: u128,
     client: reqwest::Client,
-    peers: &[String; 3],
+    relay_hub: RelayHub,
+    peers: &[PeerAddress; 3],
+    peer_channels: &[Option<SalsaBox>; 3],
+    breakers: &[CircuitBreaker; 3],
+    retry_config: PeerRetryConfig,
+    relay_timeout: Duration,
     req: UnbanRequest,
     request_id: Uuid,
 ) -> eyre::Result<UnbanResponse> {
     tracing::debug!("send unban request {request_id} to peers {peers:?}");
-    let urls = peers
-        .clone()
-        .map(|peer| format!("{peer}/api/v1/unban/{request_id}"));
-    let responses = post_to_peers(client, urls, &req.requests).await?;
+    let responses = post_to_peers(
+        rp_id,
+        client,
+        relay_hub,
+        peers,
+        RelayOp::Unban,
+        peer_channels,
+        breakers,
+        retry_config,
+        relay_timeout,
+        &req.requests,
+        request_id,
+    )
+    .await?;
     tracing::debug!("got unban response for request {request_id}");
     Ok(UnbanResponse { responses })
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn do_peer_prune(
+    rp_id: u128,
     client: reqwest::Client,
-    peers: &[String; 3],
+    relay_hub: RelayHub,
+    peers: &[PeerAddress; 3],
+    peer_channels: &[Option<SalsaBox>; 3],
+    breakers: &[CircuitBreaker; 3],
+    retry_config: PeerRetryConfig,
+    relay_timeout: Duration,
     request_id: Uuid,
 ) -> eyre::Result<()> {
     tracing::debug!("send prune request {request_id} to peers {peers:?}");
-    let urls = peers
-        .clone()
-        .map(|peer| format!("{peer}/api/v1/prune/{request_id}"));
     let req = PeerPruneRequest {};
     let requests = [req, req, req];
-    let _ = post_to_peers::<_, _, PeerPruneResponse>(client, urls, &requests).await?;
+    let _ = post_to_peers::<_, PeerPruneResponse>(
+        rp_id,
+        client,
+        relay_hub,
+        peers,
+        RelayOp::Prune,
+        peer_channels,
+        breakers,
+        retry_config,
+        relay_timeout,
+        &requests,
+        request_id,
+    )
+    .await?;
     tracing::debug!("got prune response for request {request_id}");
     Ok(())
 }
 
-async fn post_to_peers<U: IntoUrl, Req: Serialize, Res: DeserializeOwned>(
+/// Returns whether an HTTP response status is worth retrying: server errors and 429 (rate
+/// limited), but never other 4xx — those mean the peer rejected the request as-is and a retry
+/// would just get the same answer.
+fn is_retryable_status(status: reqwest::StatusCode) -> bool {
+    status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+}
+
+/// Returns whether a transport-level error (as opposed to a non-2xx response) is worth retrying:
+/// failing to connect or a timeout, not e.g. a body/decode error.
+fn is_retryable_transport_error(err: &reqwest::Error) -> bool {
+    err.is_connect() || err.is_timeout()
+}
+
+/// Applies +/-25% jitter to `backoff` (capped at `max`) so peers whose requests were issued at
+/// the same instant don't retry in lockstep.
+fn jittered_backoff(backoff: Duration, max: Duration) -> Duration {
+    let backoff = backoff.min(max);
+    let jitter = rand::thread_rng().gen_range(0.75..=1.25);
+    backoff.mul_f64(jitter)
+}
+
+/// Serializes `req` to JSON, sealing it under `channel` (see
+/// [`bitservice_types::peer_channel`]) when this peer has one configured.
+fn seal_request(req: &impl Serialize, channel: Option<&SalsaBox>) -> eyre::Result<Vec<u8>> {
+    let plaintext = serde_json::to_vec(req).context("failed to serialize request body")?;
+    match channel {
+        None => Ok(plaintext),
+        Some(channel) => {
+            let sealed = SealedEnvelope::seal(channel, &plaintext, &mut rand::thread_rng());
+            Ok(serde_json::to_vec(&sealed).context("failed to serialize sealed envelope")?)
+        }
+    }
+}
+
+/// Reverses [`seal_request`] on a peer's response bytes: opens them under `channel` when this
+/// peer has one configured, then deserializes the resulting JSON. Shared by the forward (HTTP
+/// response body) and reverse (relay frame body) transports.
+fn open_bytes<Res: DeserializeOwned>(bytes: &[u8], channel: Option<&SalsaBox>) -> eyre::Result<Res> {
+    let plaintext = match channel {
+        None => bytes.to_vec(),
+        Some(channel) => {
+            let envelope: SealedEnvelope =
+                serde_json::from_slice(bytes).context("peer response is not a sealed envelope")?;
+            envelope
+                .open(channel)
+                .map_err(|_| eyre::eyre!("failed to authenticate sealed peer response"))?
+        }
+    };
+    Ok(serde_json::from_slice(&plaintext)?)
+}
+
+/// Sends `req` to `url` with retry-with-backoff and circuit breaking per `retry_config`,
+/// returning the successful [`reqwest::Response`], or an error once retries are exhausted, the
+/// peer answered with a non-retryable status, or `breaker` is tripped and fails the request fast.
+async fn post_to_peer_http(
+    client: &reqwest::Client,
+    url: impl IntoUrl,
+    body: Vec<u8>,
+    peer_label: &'static str,
+    breaker: &CircuitBreaker,
+    retry_config: PeerRetryConfig,
+) -> eyre::Result<reqwest::Response> {
+    let url = url.into_url().context("invalid peer url")?;
+    let mut backoff = retry_config.backoff_base;
+    for attempt in 1..=retry_config.max_attempts {
+        if !breaker.admit(retry_config.circuit_breaker_cooldown) {
+            eyre::bail!("circuit breaker open for {peer_label}, failing fast");
+        }
+        let outcome = client
+            .post(url.clone())
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.clone())
+            .send()
+            .await;
+        let retryable = match &outcome {
+            Ok(response) => is_retryable_status(response.status()),
+            Err(err) => is_retryable_transport_error(err),
+        };
+        match outcome {
+            Ok(response) if response.status().is_success() => {
+                breaker.record_success();
+                return Ok(response);
+            }
+            _ if retryable && attempt < retry_config.max_attempts => {
+                breaker.record_failure(retry_config.circuit_breaker_threshold);
+                let sleep_for = jittered_backoff(backoff, retry_config.backoff_max);
+                tracing::warn!(
+                    "request to {peer_label} failed on attempt {attempt}/{}, retrying in {sleep_for:?}",
+                    retry_config.max_attempts
+                );
+                tokio::time::sleep(sleep_for).await;
+                backoff = (backoff * 2).min(retry_config.backoff_max);
+            }
+            Ok(response) => {
+                breaker.record_failure(retry_config.circuit_breaker_threshold);
+                let status = response.status();
+                let error = response
+                    .text()
+                    .await
+                    .context(format!("while reading error body from {peer_label}"))?;
+                eyre::bail!("{peer_label} returned error ({status}): {error}");
+            }
+            Err(err) => {
+                breaker.record_failure(retry_config.circuit_breaker_threshold);
+                return Err(err).context(format!("while sending request to {peer_label}"));
+            }
+        }
+    }
+    eyre::bail!(
+        "exhausted {} attempts against {peer_label}",
+        retry_config.max_attempts
+    )
+}
+
+/// Sends `req` to one peer and returns its decoded response - over HTTP with retry-with-backoff
+/// and circuit breaking when `address` is [`PeerAddress::Forward`], or over its parked relay
+/// connection (tagged with `op` so the peer knows which handler to dispatch to) when
+/// [`PeerAddress::Reverse`]. The relay path gets its own circuit breaker treatment so a peer
+/// that's not currently connected fails fast the same way a down HTTP peer does.
+#[allow(clippy::too_many_arguments)]
+async fn post_to_peer<Req: Serialize, Res: DeserializeOwned>(
+    client: &reqwest::Client,
+    relay_hub: &RelayHub,
+    address: &PeerAddress,
+    op: RelayOp,
+    request_id: Uuid,
+    req: &Req,
+    peer_channel: Option<&SalsaBox>,
+    peer_label: &'static str,
+    breaker: &CircuitBreaker,
+    retry_config: PeerRetryConfig,
+    relay_timeout: Duration,
+) -> eyre::Result<Res> {
+    let body = seal_request(req, peer_channel)?;
+    match address {
+        PeerAddress::Forward(url) => {
+            let path = match op {
+                RelayOp::Read => "read",
+                RelayOp::Ban => "ban",
+                RelayOp::Unban => "unban",
+                RelayOp::Prune => "prune",
+            };
+            let url = format!("{url}/api/v1/{path}/{request_id}");
+            let response =
+                post_to_peer_http(client, url, body, peer_label, breaker, retry_config).await?;
+            let bytes = response.bytes().await?;
+            open_bytes(&bytes, peer_channel)
+        }
+        PeerAddress::Reverse(peer_id) => {
+            if !breaker.admit(retry_config.circuit_breaker_cooldown) {
+                eyre::bail!("circuit breaker open for {peer_label}, failing fast");
+            }
+            match relay_hub
+                .send_request(peer_id, request_id, op, body, relay_timeout)
+                .await
+            {
+                Ok(response_bytes) => {
+                    breaker.record_success();
+                    open_bytes(&response_bytes, peer_channel)
+                }
+                Err(err) => {
+                    breaker.record_failure(retry_config.circuit_breaker_threshold);
+                    Err(err).context(format!("while sending relay request to {peer_label}"))
+                }
+            }
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn post_to_peers<Req: Serialize, Res: DeserializeOwned>(
+    rp_id: u128,
     client: reqwest::Client,
-    [url0, url1, url2]: [U; 3],
+    relay_hub: RelayHub,
+    peers: &[PeerAddress; 3],
+    op: RelayOp,
+    peer_channels: &[Option<SalsaBox>; 3],
+    breakers: &[CircuitBreaker; 3],
+    retry_config: PeerRetryConfig,
+    relay_timeout: Duration,
     requests: &[Req; 3],
+    request_id: Uuid,
 ) -> eyre::Result<[Res; 3]> {
     let (res0, res1, res2) = tokio::join!(
-        client.post(url0).json(&requests[0]).send(),
-        client.post(url1).json(&requests[1]).send(),
-        client.post(url2).json(&requests[2]).send(),
+        post_to_peer(
+            &client, &relay_hub, &peers[0], op, request_id, &requests[0],
+            peer_channels[0].as_ref(), "peer0", &breakers[0], retry_config, relay_timeout,
+        ),
+        post_to_peer(
+            &client, &relay_hub, &peers[1], op, request_id, &requests[1],
+            peer_channels[1].as_ref(), "peer1", &breakers[1], retry_config, relay_timeout,
+        ),
+        post_to_peer(
+            &client, &relay_hub, &peers[2], op, request_id, &requests[2],
+            peer_channels[2].as_ref(), "peer2", &breakers[2], retry_config, relay_timeout,
+        ),
     );
-    let res0 = res0.context("while sending request to peer0")?;
-    let res1 = res1.context("while sending request to peer1")?;
-    let res2 = res2.context("while sending request to peer2")?;
-    if !res0.status().is_success() {
-        let error = res0.text().await?;
-        eyre::bail!("peer0 return error: {error}");
-    }
-    if !res1.status().is_success() {
-        let error = res1.text().await?;
-        eyre::bail!("peer1 return error: {error}");
-    }
-    if !res2.status().is_success() {
-        let error = res2.text().await?;
-        eyre::bail!("peer2 return error: {error}");
-    }
-    let (res0, res1, res2) =
-        tokio::join!(res0.json::<Res>(), res1.json::<Res>(), res2.json::<Res>(),);
-    let res0 = res0.context("while receiving response from peer0")?;
-    let res1 = res1.context("while receiving response from peer1")?;
-    let res2 = res2.context("while receiving response from peer2")?;
+    let res0 = res0.inspect_err(|_| metrics::record_peer_failure(rp_id, "peer0"))?;
+    let res1 = res1.inspect_err(|_| metrics::record_peer_failure(rp_id, "peer1"))?;
+    let res2 = res2.inspect_err(|_| metrics::record_peer_failure(rp_id, "peer2"))?;
     Ok([res0, res1, res2])
 }
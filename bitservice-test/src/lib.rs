@@ -14,10 +14,24 @@ pub async fn start_server() -> String {
         rp_bitservice_peers_config: dir.join("../rp_bitservice_peers_config.toml"),
         peer_request_timeout: Duration::from_secs(60),
         prune_write_interval: 128,
+        peer_retry_max_attempts: 3,
+        peer_retry_backoff_base: Duration::from_millis(50),
+        peer_retry_backoff_max: Duration::from_secs(2),
+        peer_circuit_breaker_threshold: 5,
+        peer_circuit_breaker_cooldown: Duration::from_secs(10),
         max_num_read_tasks: 4096,
+        cache_kind: bitservice_server::config::CacheKind::InMemory,
+        cache_ttl: Duration::from_secs(30),
+        cache_max_capacity: 100_000,
+        cache_redis_url: None,
+        peer_channel_kind: bitservice_server::config::PeerChannelKind::Plain,
+        peer_channel_identity_key_path: None,
+        relay_shared_secret: None,
     };
     tokio::spawn(async move {
-        let res = bitservice_server::start(config).await;
+        let metrics_handle = bitservice_server::metrics::install_recorder()
+            .expect("failed to install metrics recorder");
+        let res = bitservice_server::start(config, metrics_handle).await;
         eprintln!("peer server to start: {res:?}");
     });
     tokio::time::timeout(Duration::from_secs(5), async {
@@ -49,6 +63,11 @@ async fn start_peer(id: u8, db_url: &str) -> String {
         next_peer: format!("ws://localhost:1{next_id:04}/api/v1/ws"),
         tcp_next_peer: format!("127.0.0.1:11{next_id:03}").parse().unwrap(),
         prev_peer_wait_timeout: Duration::from_secs(10),
+        ws_idle_timeout: Duration::from_secs(30),
+        ws_ping_interval: Duration::from_secs(10),
+        transport_kind: bitservice_peer::config::TransportKind::Plain,
+        next_peer_public_key_path: None,
+        prev_peer_public_key_path: None,
         oblivious_map_read_circuit_path: dir.join("../oblivious_map_read.json"),
         oblivious_map_write_circuit_path: dir.join("../oblivious_map_write.json"),
         secret_key_path: dir.join(format!("../dev-keys/peer{id}.sk")),
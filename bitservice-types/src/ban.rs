@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 use crate::groth16::Groth16Proof;
+use crate::mmr::ConsistencyProof;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PeerBanRequest {
@@ -19,6 +20,9 @@ pub struct PeerBanResponse {
     #[serde(serialize_with = "ark_serde_compat::serialize_bn254_fr")]
     #[serde(deserialize_with = "ark_serde_compat::deserialize_bn254_fr")]
     pub new_root: ark_bn254::Fr,
+    /// Proof that `new_root` is an append-only extension of `old_root`, i.e. this ban didn't
+    /// replace history rather than add to it. See [`crate::mmr`].
+    pub consistency_proof: ConsistencyProof,
     #[serde(serialize_with = "ark_serde_compat::serialize_bn254_fr")]
     #[serde(deserialize_with = "ark_serde_compat::deserialize_bn254_fr")]
     pub commitment_key: ark_bn254::Fr,
@@ -0,0 +1,139 @@
+//! Pluggable wire-format codecs for peer API payloads.
+//!
+//! All types in this crate serialize fine as JSON, but every bn254 field element and G1/G2
+//! point turns into a long decimal/base64 string on the wire, which inflates proof-heavy
+//! responses several-fold. The [`Codec`] trait abstracts over the wire format so callers can
+//! opt into a denser binary encoding instead, selected via the `serialize_postcard`,
+//! `serialize_bincode` and `serialize_rmp` cargo features.
+
+use serde::{Serialize, de::DeserializeOwned};
+
+/// A wire-format codec for encoding/decoding the serde types in this crate.
+///
+/// Each implementation is tied to an HTTP content type so it can be used for content
+/// negotiation (see the `Accept`/`Content-Type` handling in the peer's `api` module).
+pub trait Codec {
+    /// The MIME type this codec is negotiated with, e.g. `application/x-postcard`.
+    const CONTENT_TYPE: &'static str;
+
+    /// Encodes `value` into this codec's wire format.
+    fn encode<T: Serialize>(value: &T) -> eyre::Result<Vec<u8>>;
+
+    /// Decodes `bytes` (produced by [`Codec::encode`]) back into `T`.
+    fn decode<T: DeserializeOwned>(bytes: &[u8]) -> eyre::Result<T>;
+}
+
+/// Plain JSON. This is the format every endpoint used before pluggable codecs existed, and
+/// remains the default when a client does not ask for anything else.
+#[derive(Debug, Clone, Copy)]
+pub struct JsonCodec;
+
+impl Codec for JsonCodec {
+    const CONTENT_TYPE: &'static str = "application/json";
+
+    fn encode<T: Serialize>(value: &T) -> eyre::Result<Vec<u8>> {
+        Ok(serde_json::to_vec(value)?)
+    }
+
+    fn decode<T: DeserializeOwned>(bytes: &[u8]) -> eyre::Result<T> {
+        Ok(serde_json::from_slice(bytes)?)
+    }
+}
+
+/// [Postcard](https://docs.rs/postcard) - a compact, `no_std`-friendly binary format.
+#[cfg(feature = "serialize_postcard")]
+#[derive(Debug, Clone, Copy)]
+pub struct PostcardCodec;
+
+#[cfg(feature = "serialize_postcard")]
+impl Codec for PostcardCodec {
+    const CONTENT_TYPE: &'static str = "application/x-postcard";
+
+    fn encode<T: Serialize>(value: &T) -> eyre::Result<Vec<u8>> {
+        Ok(postcard::to_allocvec(value)?)
+    }
+
+    fn decode<T: DeserializeOwned>(bytes: &[u8]) -> eyre::Result<T> {
+        Ok(postcard::from_bytes(bytes)?)
+    }
+}
+
+/// [bincode](https://docs.rs/bincode) - the same binary format already used to seal MPC shares
+/// in the client (see `serialize_encode_seal`).
+#[cfg(feature = "serialize_bincode")]
+#[derive(Debug, Clone, Copy)]
+pub struct BincodeCodec;
+
+#[cfg(feature = "serialize_bincode")]
+impl Codec for BincodeCodec {
+    const CONTENT_TYPE: &'static str = "application/x-bincode";
+
+    fn encode<T: Serialize>(value: &T) -> eyre::Result<Vec<u8>> {
+        Ok(bincode::serde::encode_to_vec(
+            value,
+            bincode::config::standard(),
+        )?)
+    }
+
+    fn decode<T: DeserializeOwned>(bytes: &[u8]) -> eyre::Result<T> {
+        let (value, _) =
+            bincode::serde::decode_from_slice(bytes, bincode::config::standard())?;
+        Ok(value)
+    }
+}
+
+/// [MessagePack](https://docs.rs/rmp-serde) - a self-describing binary format, useful for
+/// clients that want binary payloads without pinning the exact Rust struct layout.
+#[cfg(feature = "serialize_rmp")]
+#[derive(Debug, Clone, Copy)]
+pub struct MessagePackCodec;
+
+#[cfg(feature = "serialize_rmp")]
+impl Codec for MessagePackCodec {
+    const CONTENT_TYPE: &'static str = "application/x-msgpack";
+
+    fn encode<T: Serialize>(value: &T) -> eyre::Result<Vec<u8>> {
+        Ok(rmp_serde::to_vec(value)?)
+    }
+
+    fn decode<T: DeserializeOwned>(bytes: &[u8]) -> eyre::Result<T> {
+        Ok(rmp_serde::from_slice(bytes)?)
+    }
+}
+
+/// Picks the codec to encode a response with based on a client-supplied `Accept` header value,
+/// falling back to [`JsonCodec`] if the header is absent or names a format we don't support.
+pub fn encode_for_accept<T: Serialize>(accept: Option<&str>, value: &T) -> eyre::Result<(Vec<u8>, &'static str)> {
+    match accept {
+        #[cfg(feature = "serialize_postcard")]
+        Some(ct) if ct.contains(PostcardCodec::CONTENT_TYPE) => {
+            Ok((PostcardCodec::encode(value)?, PostcardCodec::CONTENT_TYPE))
+        }
+        #[cfg(feature = "serialize_bincode")]
+        Some(ct) if ct.contains(BincodeCodec::CONTENT_TYPE) => {
+            Ok((BincodeCodec::encode(value)?, BincodeCodec::CONTENT_TYPE))
+        }
+        #[cfg(feature = "serialize_rmp")]
+        Some(ct) if ct.contains(MessagePackCodec::CONTENT_TYPE) => {
+            Ok((MessagePackCodec::encode(value)?, MessagePackCodec::CONTENT_TYPE))
+        }
+        _ => Ok((JsonCodec::encode(value)?, JsonCodec::CONTENT_TYPE)),
+    }
+}
+
+/// Decodes a request body according to its `Content-Type` header, falling back to JSON when
+/// the header is absent or unrecognized.
+pub fn decode_for_content_type<T: DeserializeOwned>(
+    content_type: Option<&str>,
+    bytes: &[u8],
+) -> eyre::Result<T> {
+    match content_type {
+        #[cfg(feature = "serialize_postcard")]
+        Some(ct) if ct.contains(PostcardCodec::CONTENT_TYPE) => PostcardCodec::decode(bytes),
+        #[cfg(feature = "serialize_bincode")]
+        Some(ct) if ct.contains(BincodeCodec::CONTENT_TYPE) => BincodeCodec::decode(bytes),
+        #[cfg(feature = "serialize_rmp")]
+        Some(ct) if ct.contains(MessagePackCodec::CONTENT_TYPE) => MessagePackCodec::decode(bytes),
+        _ => JsonCodec::decode(bytes),
+    }
+}
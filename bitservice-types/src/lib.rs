@@ -1,6 +1,15 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+pub mod ban;
+pub mod codec;
+pub mod groth16;
+pub mod mmr;
+pub mod peer_channel;
+pub mod read;
+pub mod relay;
+pub mod unban;
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct PeerReadRequest {
     pub request_id: Uuid,
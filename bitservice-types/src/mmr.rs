@@ -0,0 +1,326 @@
+//! Merkle Mountain Range accumulator over the map's committed root history.
+//!
+//! Each time the oblivious map commits a write/ban/unban, its new root is appended as a leaf
+//! here. Unlike a conventional Merkle tree, an MMR never needs to be rebuilt or re-balanced as it
+//! grows: appending a leaf only ever merges the two most-recent equal-height perfect subtrees
+//! (`parent = H(left || right)`), so appends are O(log n) amortized and the overall root is just
+//! the fold, high to low, of the outstanding subtree ("peak") roots.
+//!
+//! Because the structure is append-only, a peak of the tree at any earlier size `m` is always
+//! either still a peak at the current size `n`, or has been merged, whole, into some larger peak
+//! of the size-`n` tree - it is never split or reordered. That's what makes a consistency proof
+//! between `old_root` (size `m`) and `new_root` (size `n`) possible: walk each size-`m` peak up
+//! through the merges it was absorbed into until it lands on a size-`n` peak, and hand the
+//! verifier the sibling hash seen at every step.
+//!
+//! The pairing hash must match the in-circuit hash used for the Groth16 statement, so it reuses
+//! the same Poseidon2 permutation (with feed-forward) that the indexer's identity tree uses.
+
+use ark_bn254::Fr;
+use ark_ff::Zero;
+use poseidon2::{POSEIDON2_BN254_T2_PARAMS, Poseidon2};
+use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
+
+static POSEIDON_HASHER: LazyLock<Poseidon2<Fr, 2, 5>> =
+    LazyLock::new(|| Poseidon2::new(&POSEIDON2_BN254_T2_PARAMS));
+
+/// The root of an empty (zero-leaf) accumulator.
+pub fn empty_root() -> Fr {
+    Fr::zero()
+}
+
+fn hash_pair(left: Fr, right: Fr) -> Fr {
+    let mut state = [left, right];
+    let feed_forward = state[0];
+    POSEIDON_HASHER.permutation_in_place(&mut state);
+    state[0] += feed_forward;
+    state[0]
+}
+
+/// Folds a list of peak roots, ordered high-to-low (oldest/largest subtree first), into a single
+/// root, the same way [`Mmr::root`] does.
+fn fold_peaks(peaks: &[Fr]) -> Option<Fr> {
+    let mut iter = peaks.iter().copied();
+    let mut acc = iter.next()?;
+    for peak in iter {
+        acc = hash_pair(acc, peak);
+    }
+    Some(acc)
+}
+
+/// Which side of a merge a node occupied, needed to recompute `H(left || right)` while walking a
+/// [`ConsistencyProof`] up to a current peak.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+/// The merge path for a single peak of the size-`m` tree: the sibling hash and side seen at every
+/// merge it was absorbed into on the way to becoming (part of) a peak of the size-`n` tree. Empty
+/// if that peak is still unmerged, i.e. it's also a peak of the size-`n` tree.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MergePath {
+    #[serde(with = "fr_vec_serde")]
+    pub siblings: Vec<Fr>,
+    pub sides: Vec<Side>,
+}
+
+/// A proof that the size-`n` tree (`new_root`) is an append-only extension of the size-`m` tree
+/// (`old_root`). When `m == 0`, `old_peaks` and `merge_paths` are empty and `new_peaks` holds
+/// every peak of the size-`n` tree (so the proof is fully empty only when `n == 0` as well); when
+/// `m == n` it still holds `old_peaks` but every `merge_path` is empty and `new_peaks` is empty,
+/// so it trivially folds back to `old_root == new_root`. See [`verify_consistency`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConsistencyProof {
+    /// Peaks of the size-`m` tree, high-to-low.
+    #[serde(with = "fr_vec_serde")]
+    pub old_peaks: Vec<Fr>,
+    /// One entry per `old_peaks`, in the same order.
+    pub merge_paths: Vec<MergePath>,
+    /// Peaks of the size-`n` tree covering leaves `m..n`, i.e. not reachable by climbing any
+    /// `old_peaks` entry.
+    #[serde(with = "fr_vec_serde")]
+    pub new_peaks: Vec<Fr>,
+}
+
+/// Verifies that `proof` demonstrates `new_root` is an append-only extension of `old_root`.
+///
+/// A verifier doesn't need to know the tree sizes `m`/`n`: an empty `proof.old_peaks` encodes
+/// `m == 0` (nothing to prove beyond `old_root` being the canonical empty-tree root), and when
+/// `m == n` the proof degenerates to `old_peaks == new_peaks`-after-climbing with no leftover
+/// `new_peaks`, which the fold below already confirms equals `old_root == new_root`.
+pub fn verify_consistency(old_root: Fr, new_root: Fr, proof: &ConsistencyProof) -> bool {
+    if proof.old_peaks.is_empty() {
+        if old_root != empty_root() {
+            return false;
+        }
+        return match fold_peaks(&proof.new_peaks) {
+            Some(root) => root == new_root,
+            None => new_root == empty_root(),
+        };
+    }
+    if proof.old_peaks.len() != proof.merge_paths.len() {
+        return false;
+    }
+    if fold_peaks(&proof.old_peaks) != Some(old_root) {
+        return false;
+    }
+
+    // Multiple old peaks can climb to the same current peak - fold each distinct result in once.
+    let mut seen = Vec::with_capacity(proof.old_peaks.len());
+    let mut climbed = Vec::with_capacity(proof.old_peaks.len());
+    for (peak, path) in proof.old_peaks.iter().zip(&proof.merge_paths) {
+        if path.siblings.len() != path.sides.len() {
+            return false;
+        }
+        let mut acc = *peak;
+        for (sibling, side) in path.siblings.iter().zip(&path.sides) {
+            acc = match side {
+                Side::Left => hash_pair(acc, *sibling),
+                Side::Right => hash_pair(*sibling, acc),
+            };
+        }
+        if !seen.contains(&acc) {
+            seen.push(acc);
+            climbed.push(acc);
+        }
+    }
+    climbed.extend(proof.new_peaks.iter().copied());
+
+    fold_peaks(&climbed) == Some(new_root)
+}
+
+/// An append-only Merkle Mountain Range over `Fr` leaves.
+#[derive(Debug, Clone, Default)]
+pub struct Mmr {
+    nodes: Vec<Fr>,
+    heights: Vec<u32>,
+    /// `Some((parent, sibling_hash, side))` once a node has been merged into a parent; `None`
+    /// while it's still an unmerged peak.
+    climb: Vec<Option<(usize, Fr, Side)>>,
+    /// Peak node indices after each append, `peaks_by_size[k - 1]` being the peaks when the tree
+    /// had `k` leaves. Lets us produce a consistency proof against any earlier size without
+    /// re-deriving the tree shape.
+    peaks_by_size: Vec<Vec<usize>>,
+}
+
+impl Mmr {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of leaves appended so far.
+    pub fn len(&self) -> u64 {
+        self.peaks_by_size.len() as u64
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.peaks_by_size.is_empty()
+    }
+
+    /// The current root: the fold, high-to-low, of the outstanding peak roots.
+    pub fn root(&self) -> Fr {
+        match self.peaks_by_size.last() {
+            None => empty_root(),
+            Some(peaks) => {
+                fold_peaks(&peaks.iter().map(|&i| self.nodes[i]).collect::<Vec<_>>())
+                    .expect("at least one peak once the tree is non-empty")
+            }
+        }
+    }
+
+    /// Appends a leaf, returning the new root.
+    pub fn push(&mut self, leaf: Fr) -> Fr {
+        self.nodes.push(leaf);
+        self.heights.push(0);
+        self.climb.push(None);
+
+        let mut peaks = self.peaks_by_size.last().cloned().unwrap_or_default();
+        peaks.push(self.nodes.len() - 1);
+
+        while peaks.len() >= 2 {
+            let r = peaks[peaks.len() - 1];
+            let l = peaks[peaks.len() - 2];
+            if self.heights[l] != self.heights[r] {
+                break;
+            }
+
+            let parent_hash = hash_pair(self.nodes[l], self.nodes[r]);
+            let parent_height = self.heights[l] + 1;
+            self.nodes.push(parent_hash);
+            self.heights.push(parent_height);
+            self.climb.push(None);
+            let parent = self.nodes.len() - 1;
+
+            self.climb[l] = Some((parent, self.nodes[r], Side::Left));
+            self.climb[r] = Some((parent, self.nodes[l], Side::Right));
+
+            peaks.pop();
+            peaks.pop();
+            peaks.push(parent);
+        }
+
+        self.peaks_by_size.push(peaks);
+        self.root()
+    }
+
+    /// Walks `idx` up through recorded merges until it lands on a node that is still an unmerged
+    /// peak, returning that peak's node index, the path of (sibling, side) pairs seen along the
+    /// way, and the hash obtained by folding `idx`'s own hash through that path (which must equal
+    /// the landed peak's hash).
+    fn climb_to_current_peak(&self, mut idx: usize) -> (usize, MergePath, Fr) {
+        let mut path = MergePath::default();
+        let mut acc = self.nodes[idx];
+        while let Some((parent, sibling, side)) = self.climb[idx] {
+            path.siblings.push(sibling);
+            path.sides.push(side);
+            acc = match side {
+                Side::Left => hash_pair(acc, sibling),
+                Side::Right => hash_pair(sibling, acc),
+            };
+            idx = parent;
+        }
+        (idx, path, acc)
+    }
+
+    /// Produces a proof that the size-`m` tree is a prefix of this (size-`n`) tree.
+    ///
+    /// Fully empty only when the tree itself is empty. If `m == 0`, `old_peaks` is empty and
+    /// `new_peaks` holds every current peak; if `m == self.len()`, every merge path and
+    /// `new_peaks` are empty, so the proof folds straight back to the current root.
+    pub fn consistency_proof(&self, m: u64) -> ConsistencyProof {
+        let n = self.len();
+        assert!(m <= n, "m must not exceed the current size");
+        if n == 0 {
+            return ConsistencyProof::default();
+        }
+
+        let old_peak_indices = if m == 0 {
+            Vec::new()
+        } else {
+            self.peaks_by_size[(m - 1) as usize].clone()
+        };
+        let new_peak_indices = &self.peaks_by_size[(n - 1) as usize];
+
+        // Multiple old peaks can climb to the same current peak (e.g. two adjacent old peaks
+        // that later merged together and beyond); key by landing index so we don't double-count
+        // it when folding the result.
+        let mut landed: std::collections::HashMap<usize, Fr> = std::collections::HashMap::new();
+        let mut old_peaks = Vec::with_capacity(old_peak_indices.len());
+        let mut merge_paths = Vec::with_capacity(old_peak_indices.len());
+        for idx in old_peak_indices {
+            old_peaks.push(self.nodes[idx]);
+            let (landed_idx, path, climbed_hash) = self.climb_to_current_peak(idx);
+            landed.insert(landed_idx, climbed_hash);
+            merge_paths.push(path);
+        }
+
+        let new_peaks = new_peak_indices
+            .iter()
+            .filter(|idx| !landed.contains_key(idx))
+            .map(|&idx| self.nodes[idx])
+            .collect();
+
+        ConsistencyProof {
+            old_peaks,
+            merge_paths,
+            new_peaks,
+        }
+    }
+}
+
+/// Serializes a `Vec<Fr>` by delegating each element to `ark_serde_compat`'s single-`Fr`
+/// (de)serializers, the same ones used for the bare `Fr` fields throughout this crate.
+mod fr_vec_serde {
+    use ark_bn254::Fr;
+    use serde::de::{SeqAccess, Visitor};
+    use serde::ser::SerializeSeq;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::fmt;
+
+    struct Elem(Fr);
+
+    impl Serialize for Elem {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            ark_serde_compat::serialize_bn254_fr(&self.0, serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Elem {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            Ok(Elem(ark_serde_compat::deserialize_bn254_fr(deserializer)?))
+        }
+    }
+
+    pub fn serialize<S: Serializer>(values: &[Fr], serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(values.len()))?;
+        for value in values {
+            seq.serialize_element(&Elem(*value))?;
+        }
+        seq.end()
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Fr>, D::Error> {
+        struct FrSeqVisitor;
+
+        impl<'de> Visitor<'de> for FrSeqVisitor {
+            type Value = Vec<Fr>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a sequence of bn254 field elements")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut out = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+                while let Some(Elem(fr)) = seq.next_element()? {
+                    out.push(fr);
+                }
+                Ok(out)
+            }
+        }
+
+        deserializer.deserialize_seq(FrSeqVisitor)
+    }
+}
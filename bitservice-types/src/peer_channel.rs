@@ -0,0 +1,39 @@
+//! Wire format for the authenticated, encrypted channel between the bitservice-server
+//! orchestrator and a peer's v1 API.
+//!
+//! Bodies are sealed under a [`SalsaBox`] built from the orchestrator's static secret key and
+//! the peer's static public key - the same construction `ws_mpc_net::auth` uses for its box,
+//! just applied per HTTP request/response instead of per frame of a persistent link, so no
+//! handshake or nonce-counter state needs to survive across independent requests.
+
+use crypto_box::{
+    SalsaBox,
+    aead::{Aead, generic_array::GenericArray},
+};
+use rand::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+
+/// A request or response body sealed for one peer channel.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SealedEnvelope {
+    pub nonce: [u8; 24],
+    pub ciphertext: Vec<u8>,
+}
+
+impl SealedEnvelope {
+    /// Seals `plaintext` under `channel` with a fresh random nonce.
+    pub fn seal<R: RngCore + CryptoRng>(channel: &SalsaBox, plaintext: &[u8], rng: &mut R) -> Self {
+        let mut nonce = [0u8; 24];
+        rng.fill_bytes(&mut nonce);
+        let ciphertext = channel
+            .encrypt(GenericArray::from_slice(&nonce), plaintext)
+            .expect("encryption under a valid key cannot fail");
+        Self { nonce, ciphertext }
+    }
+
+    /// Opens the envelope under `channel`, failing if it wasn't sealed for this channel or has
+    /// been tampered with.
+    pub fn open(&self, channel: &SalsaBox) -> Result<Vec<u8>, crypto_box::aead::Error> {
+        channel.decrypt(GenericArray::from_slice(&self.nonce), self.ciphertext.as_ref())
+    }
+}
@@ -0,0 +1,36 @@
+//! Wire format for the reverse ("peer dials in") transport between the bitservice-server
+//! orchestrator and a peer - see `bitservice_server::relay` (the dial-in relay hub) and
+//! `bitservice_peer::relay_client` (the peer's dial-out loop).
+//!
+//! The forward HTTP transport encodes which v1 operation a request is for in the URL path; a
+//! relay connection has no path, so [`RelayFrame`] carries it explicitly as `op`.
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Header a peer dialing `/relay/{peer_id}` must present the configured shared secret under -
+/// without it, any client could claim any `peer_id` and start receiving that peer's traffic. See
+/// `bitservice_server::relay::RelayHub::authorize` and `bitservice_peer::relay_client::dial`.
+pub const RELAY_SHARED_SECRET_HEADER: &str = "x-relay-shared-secret";
+
+/// Which v1 operation a [`RelayFrame`] request carries.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum RelayOp {
+    Read,
+    Ban,
+    Unban,
+    Prune,
+}
+
+/// One request or response exchanged over a relay connection.
+///
+/// `body` is exactly what the forward HTTP transport would have sent/received as the request or
+/// response payload - plain or `SealedEnvelope`-sealed JSON, depending on `peer_channel_kind` -
+/// so neither side of `peer_channel` needs to know which transport carried it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelayFrame {
+    pub request_id: Uuid,
+    /// `Some` on a request; `None` on the matching response, which doesn't need to repeat it.
+    pub op: Option<RelayOp>,
+    pub body: Vec<u8>,
+}
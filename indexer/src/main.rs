@@ -2,6 +2,7 @@ use anyhow::Result;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 mod rp_indexer;
+mod store;
 
 #[tokio::main]
 async fn main() -> Result<()> {
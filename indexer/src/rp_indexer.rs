@@ -1,21 +1,28 @@
+use std::collections::VecDeque;
+use std::convert::Infallible;
 use std::net::SocketAddr;
 use std::sync::LazyLock;
 
+use alloy::eips::BlockNumberOrTag;
 use alloy::primitives::{Address, U256};
 use alloy::providers::{Provider, ProviderBuilder};
 use alloy::rpc::types::Filter;
 use alloy::sol;
 use alloy::sol_types::SolEvent;
 use ark_bn254::Fr;
-use axum::extract::{Path, State};
+use axum::extract::{Path, Query, State};
+use axum::response::sse::{Event, KeepAlive, Sse};
 use axum::response::IntoResponse;
+use futures::{stream, Stream, StreamExt as _};
 use poseidon2::{Poseidon2, POSEIDON2_BN254_T2_PARAMS};
 use semaphore_rs_hasher::Hasher;
-use semaphore_rs_trees::lazy::{Canonical, LazyMerkleTree as MerkleTree};
+use semaphore_rs_trees::lazy::{Canonical, Derived, LazyMerkleTree as MerkleTree};
 use semaphore_rs_trees::proof::InclusionProof;
 use semaphore_rs_trees::Branch;
-use sqlx::{postgres::PgPoolOptions, PgPool};
-use tokio::sync::RwLock;
+use tokio::sync::{broadcast, RwLock};
+use tokio_stream::wrappers::BroadcastStream;
+
+use crate::store::{AccountStore, EmbeddedStore, PostgresStore};
 
 // Contract events
 sol! {
@@ -54,6 +61,34 @@ impl Hasher for PoseidonHasher {
 pub static GLOBAL_TREE: LazyLock<RwLock<MerkleTree<PoseidonHasher, Canonical>>> =
     LazyLock::new(|| RwLock::new(MerkleTree::<PoseidonHasher>::new(TREE_DEPTH, U256::ZERO)));
 
+// Bounded window of recent tree versions, one per verified `RootRecorded` epoch, so
+// `/proof/:index` can answer against a historical root instead of only the live tree.
+// `derived()` snapshots are cheap (the lazy tree shares structure with its canonical
+// parent), so keeping a window of these costs little beyond the live tree itself.
+// Front = most recent epoch, back = oldest retained.
+static TREE_HISTORY: LazyLock<RwLock<VecDeque<(u64, MerkleTree<PoseidonHasher, Derived>)>>> =
+    LazyLock::new(|| RwLock::new(VecDeque::new()));
+
+
+// Which `AccountStore` backend to run against. Postgres is the historical default; embedded
+// lets an operator run the indexer as a single binary with no external DB.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StoreBackend {
+    Postgres,
+    Embedded,
+}
+
+impl std::str::FromStr for StoreBackend {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "postgres" => Ok(Self::Postgres),
+            "embedded" => Ok(Self::Embedded),
+            other => anyhow::bail!("unknown STORE_BACKEND '{other}', expected 'postgres' or 'embedded'"),
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct Config {
@@ -62,6 +97,10 @@ pub struct Config {
     pub registry_address: Address,
     pub start_block: u64,
     pub http_addr: SocketAddr,
+    pub max_reorg_depth: u64,
+    pub store_backend: StoreBackend,
+    pub embedded_store_path: String,
+    pub root_history: usize,
 }
 
 
@@ -69,7 +108,7 @@ pub struct Config {
 impl Config {
     pub fn from_env() -> anyhow::Result<Self> {
         Ok(Self {
-            db_url: std::env::var("DATABASE_URL")?,
+            db_url: std::env::var("DATABASE_URL").unwrap_or_default(),
             rpc_url: std::env::var("RPC_URL")?,
             registry_address: std::env::var("REGISTRY_ADDRESS")?.parse()?,
             start_block: std::env::var("START_BLOCK")
@@ -78,6 +117,17 @@ impl Config {
             http_addr: std::env::var("HTTP_ADDR")
                 .unwrap_or_else(|_| "0.0.0.0:8080".to_string())
                 .parse()?,
+            max_reorg_depth: std::env::var("MAX_REORG_DEPTH")
+                .unwrap_or_else(|_| "1000".to_string())
+                .parse()?,
+            store_backend: std::env::var("STORE_BACKEND")
+                .unwrap_or_else(|_| "postgres".to_string())
+                .parse()?,
+            embedded_store_path: std::env::var("EMBEDDED_STORE_PATH")
+                .unwrap_or_else(|_| "./rp-indexer-data".to_string()),
+            root_history: std::env::var("ROOT_HISTORY")
+                .unwrap_or_else(|_| "64".to_string())
+                .parse()?,
         })
     }
 }
@@ -100,48 +150,74 @@ struct RootInfo {
     block_number: u64,
 }
 
+// Push side for `/subscribe/roots`: every verified `RootRecorded` is published here so
+// subscribers learn about new epoch roots the instant they land, instead of polling
+// `/latest-root`. Bounded so a slow/dead subscriber can't pin memory; it'll just miss a few
+// roots and `BroadcastStream` surfaces that as a lagged error we drop on the floor.
+static ROOT_NOTIFICATIONS: LazyLock<broadcast::Sender<RootNotification>> =
+    LazyLock::new(|| broadcast::channel(64).0);
+
+#[derive(Debug, Clone)]
+struct RootNotification {
+    root: U256,
+    timestamp: u64,
+    epoch: u64,
+    block_number: u64,
+    computed_root_matches: bool,
+}
+
+fn root_notification_event(notification: &RootNotification) -> Event {
+    Event::default()
+        .json_data(serde_json::json!({
+            "root": format!("0x{:x}", notification.root),
+            "epoch": notification.epoch,
+            "timestamp": notification.timestamp,
+            "block_number": notification.block_number,
+            "computed_root_matches": notification.computed_root_matches,
+        }))
+        .expect("RootNotification always serializes to JSON")
+}
+
 // Main indexer entry point
 pub async fn run_indexer() -> anyhow::Result<()> {
     let cfg = Config::from_env()?;
 
-    let pool = PgPoolOptions::new()
-        .max_connections(10) //TODO: No idea here perhaps a config value..?
-        .connect(&cfg.db_url)
-        .await?;
-
-    sqlx::migrate!("./migrations").run(&pool).await?;
+    match cfg.store_backend {
+        StoreBackend::Postgres => {
+            let store = PostgresStore::connect(&cfg.db_url).await?;
+            run_indexer_with_store(cfg, store).await
+        }
+        StoreBackend::Embedded => {
+            let store = EmbeddedStore::open(&cfg.embedded_store_path)?;
+            run_indexer_with_store(cfg, store).await
+        }
+    }
+}
 
+async fn run_indexer_with_store<S: AccountStore>(cfg: Config, store: S) -> anyhow::Result<()> {
     tracing::info!("Building merkle tree from database...");
-    build_tree_from_db(&pool).await?;
+    build_tree_from_db(&store).await?;
 
     // Start HTTP server
-    let http_pool = pool.clone();
+    let http_store = store.clone();
     let http_addr = cfg.http_addr;
     tokio::spawn(async move {
-        if let Err(e) = start_http_server(http_addr, http_pool).await {
+        if let Err(e) = start_http_server(http_addr, http_store).await {
             tracing::error!(?e, "HTTP server failed");
         }
     });
 
-    index_events(&cfg, &pool).await
+    index_events(&cfg, &store).await
 }
 
 // Build the merkle tree from existing database entries
-async fn build_tree_from_db(pool: &PgPool) -> anyhow::Result<()> {
-    let rows = sqlx::query(
-        "SELECT account_index, identity_commitment FROM accounts ORDER BY account_index ASC"
-    )
-    .fetch_all(pool)
-    .await?;
-
-    tracing::info!("Found {} rows in database", rows.len());
+async fn build_tree_from_db<S: AccountStore>(store: &S) -> anyhow::Result<()> {
+    let leaves_raw = store.iter_accounts_ordered().await?;
 
-    let mut leaves: Vec<(usize, U256)> = Vec::with_capacity(rows.len());
-    for row in rows {
-        let account_index: String = row.get("account_index");
-        let commitment: String = row.get("identity_commitment");
+    tracing::info!("Found {} rows in database", leaves_raw.len());
 
-        let index: U256 = account_index.parse()?;
+    let mut leaves: Vec<(usize, U256)> = Vec::with_capacity(leaves_raw.len());
+    for (index, commitment) in leaves_raw {
         if index == U256::ZERO {
             // TODO: Question: Will this ever happen..?
             tracing::warn!("Found account with zero index");
@@ -150,9 +226,8 @@ async fn build_tree_from_db(pool: &PgPool) -> anyhow::Result<()> {
 
         // Account indices start at 1, tree indices start at 0
         let tree_index = index.as_limbs()[0] as usize - 1;
-        let leaf_value: U256 = commitment.parse()?;
 
-        leaves.push((tree_index, leaf_value));
+        leaves.push((tree_index, commitment));
     }
 
     // Build new tree with all leaves
@@ -200,13 +275,22 @@ async fn update_tree_with_account(
     Ok(())
 }
 
-async fn index_events(cfg: &Config, pool: &PgPool) -> anyhow::Result<()> {
+async fn index_events<S: AccountStore>(cfg: &Config, store: &S) -> anyhow::Result<()> {
     let provider = ProviderBuilder::new()
         .connect_http(cfg.rpc_url.parse()?);
 
-    let mut from_block = load_checkpoint(pool).await?.unwrap_or(cfg.start_block);
+    let mut from_block = store.load_checkpoint().await?.unwrap_or(cfg.start_block);
 
     loop {
+        if from_block > 0 {
+            if let Some(ancestor) =
+                check_for_reorg(cfg, &provider, store, from_block - 1).await?
+            {
+                tracing::warn!(ancestor, "reorg detected, rolled back to common ancestor");
+                from_block = ancestor + 1;
+            }
+        }
+
         let to_block = provider.get_block_number().await?;
 
         if from_block > to_block {
@@ -234,18 +318,131 @@ async fn index_events(cfg: &Config, pool: &PgPool) -> anyhow::Result<()> {
             );
         }
 
-        for log in logs {
-            if let Err(e) = process_log(pool, &log).await {
+        for log in &logs {
+            if let Err(e) = process_log(store, log, cfg.root_history).await {
                 tracing::error!(?e, ?log, "failed to process log");
             }
+            if let (Some(number), Some(hash)) = (log.block_number, log.block_hash) {
+                store.save_block_hash(number, &format!("{hash:?}")).await?;
+            }
         }
 
-        save_checkpoint(pool, batch_end).await?;
+        // Always record a hash for the batch boundary, even when it had no logs of our own,
+        // so `check_for_reorg` always has something recent to compare against.
+        let batch_end_hash = get_block_hash(&provider, batch_end).await?;
+        store.save_block_hash(batch_end, &batch_end_hash).await?;
+
+        store.save_checkpoint(batch_end).await?;
         from_block = batch_end + 1;
     }
 }
 
-async fn process_log(pool: &PgPool, log: &alloy::rpc::types::Log) -> anyhow::Result<()> {
+async fn get_block_hash(
+    provider: &impl Provider,
+    number: u64
+) -> anyhow::Result<String> {
+    let block = provider
+        .get_block_by_number(BlockNumberOrTag::Number(number))
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("block {} not found on chain", number))?;
+
+    Ok(format!("{:?}", block.header.hash))
+}
+
+// Compare our stored hash for `last_processed` against the chain, and if it no longer
+// matches, walk backwards until we find a block where the hashes agree, then roll back
+// everything recorded after that point. Returns the common ancestor block if a reorg was
+// found and rolled back, or `None` if we're still on the canonical chain.
+async fn check_for_reorg<S: AccountStore>(
+    cfg: &Config,
+    provider: &impl Provider,
+    store: &S,
+    last_processed: u64,
+) -> anyhow::Result<Option<u64>> {
+    let Some(stored_hash) = store.load_block_hash(last_processed).await? else {
+        // We never recorded a hash for this height (e.g. it had no logs in an older batch
+        // before this feature existed) - nothing to compare against.
+        return Ok(None);
+    };
+
+    let chain_hash = get_block_hash(provider, last_processed).await?;
+    if stored_hash == chain_hash {
+        return Ok(None);
+    }
+
+    tracing::warn!(
+        block = last_processed,
+        stored_hash,
+        chain_hash,
+        "block hash mismatch, searching for common ancestor"
+    );
+
+    let mut candidate = last_processed;
+    let ancestor = loop {
+        if last_processed.saturating_sub(candidate) > cfg.max_reorg_depth {
+            anyhow::bail!(
+                "reorg deeper than max_reorg_depth ({}), refusing to roll back further",
+                cfg.max_reorg_depth
+            );
+        }
+
+        if candidate == 0 {
+            break 0;
+        }
+        candidate -= 1;
+
+        let Some(stored) = store.load_block_hash(candidate).await? else {
+            // No stored hash at this height - keep walking back until we find one to compare.
+            continue;
+        };
+        let chain = get_block_hash(provider, candidate).await?;
+        if stored == chain {
+            break candidate;
+        }
+    };
+
+    store.rollback_to(ancestor).await?;
+    store.save_checkpoint(ancestor).await?;
+
+    tracing::info!("rebuilding merkle tree after reorg rollback");
+    build_tree_from_db(store).await?;
+    reset_cached_roots_after_rollback(store).await?;
+
+    Ok(Some(ancestor))
+}
+
+// `TREE_HISTORY`'s epoch snapshots and `LATEST_ROOT` were built from the abandoned fork and no
+// longer correspond to any canonical chain state once `rollback_to` has dropped the roots
+// recorded after the reorg's common ancestor - clear the former and recompute the latter from
+// whatever root (if any) survived the rollback, the same way `build_tree_from_db` recomputes
+// `GLOBAL_TREE` from the rolled-back account rows.
+async fn reset_cached_roots_after_rollback<S: AccountStore>(store: &S) -> anyhow::Result<()> {
+    TREE_HISTORY.write().await.clear();
+
+    let mut root_info = LATEST_ROOT.write().await;
+    *root_info = match store.recent_roots(1).await?.into_iter().next() {
+        Some(record) => RootInfo {
+            root: record.root,
+            timestamp: record.timestamp,
+            epoch: record.epoch,
+            block_number: record.block_number,
+        },
+        None => RootInfo {
+            root: U256::ZERO,
+            timestamp: 0,
+            epoch: 0,
+            block_number: 0,
+        },
+    };
+
+    Ok(())
+}
+
+async fn process_log<S: AccountStore>(
+    store: &S,
+    log: &alloy::rpc::types::Log,
+    root_history: usize,
+) -> anyhow::Result<()> {
     if log.topics().is_empty() {
         return Ok(());
     }
@@ -257,17 +454,9 @@ async fn process_log(pool: &PgPool, log: &alloy::rpc::types::Log) -> anyhow::Res
     if sig == RpAccountRegistry::AccountAdded::SIGNATURE_HASH {
         let event = RpAccountRegistry::AccountAdded::decode_log(log.log_decode()?, true)?;
 
-        sqlx::query(
-            r#"INSERT INTO accounts (account_index, identity_commitment, block_number, tx_hash)
-               VALUES ($1, $2, $3, $4)
-               ON CONFLICT (account_index) DO NOTHING"#
-        )
-        .bind(event.accountIndex.to_string())
-        .bind(event.identityCommitment.to_string())
-        .bind(block_number as i64)
-        .bind(&tx_hash)
-        .execute(pool)
-        .await?;
+        store
+            .insert_account(event.accountIndex, event.identityCommitment, block_number, &tx_hash)
+            .await?;
 
         // Update merkle tree
         if let Err(e) = update_tree_with_account(event.accountIndex, event.identityCommitment).await {
@@ -282,34 +471,15 @@ async fn process_log(pool: &PgPool, log: &alloy::rpc::types::Log) -> anyhow::Res
     } else if sig == RpAccountRegistry::AccountUpdated::SIGNATURE_HASH {
         let event = RpAccountRegistry::AccountUpdated::decode_log(log.log_decode()?, true)?;
 
-        sqlx::query(
-            r#"UPDATE accounts
-               SET identity_commitment = $2,
-                   block_number = $3,
-                   tx_hash = $4,
-                   updated_at = NOW()
-               WHERE account_index = $1"#
-        )
-        .bind(event.accountIndex.to_string())
-        .bind(event.newIdentityCommitment.to_string())
-        .bind(block_number as i64)
-        .bind(&tx_hash)
-        .execute(pool)
-        .await?;
-
-        // Log the update event
-        sqlx::query(
-            r#"INSERT INTO account_updates
-               (account_index, old_commitment, new_commitment, block_number, tx_hash)
-               VALUES ($1, $2, $3, $4, $5)"#
-        )
-        .bind(event.accountIndex.to_string())
-        .bind(event.oldIdentityCommitment.to_string())
-        .bind(event.newIdentityCommitment.to_string())
-        .bind(block_number as i64)
-        .bind(&tx_hash)
-        .execute(pool)
-        .await?;
+        store
+            .apply_update(
+                event.accountIndex,
+                event.oldIdentityCommitment,
+                event.newIdentityCommitment,
+                block_number,
+                &tx_hash,
+            )
+            .await?;
 
         // Update merkle tree
         if let Err(e) = update_tree_with_account(event.accountIndex, event.newIdentityCommitment).await {
@@ -324,17 +494,9 @@ async fn process_log(pool: &PgPool, log: &alloy::rpc::types::Log) -> anyhow::Res
     } else if sig == RpAccountRegistry::RootRecorded::SIGNATURE_HASH {
         let event = RpAccountRegistry::RootRecorded::decode_log(log.log_decode()?, true)?;
 
-        sqlx::query(
-            r#"INSERT INTO roots (root, timestamp, epoch, block_number, tx_hash)
-               VALUES ($1, $2, $3, $4, $5)"#
-        )
-        .bind(event.root.to_string())
-        .bind(event.timestamp as i64)
-        .bind(event.rootEpoch as i64)
-        .bind(block_number as i64)
-        .bind(&tx_hash)
-        .execute(pool)
-        .await?;
+        store
+            .record_root(event.root, event.timestamp, event.rootEpoch, block_number, &tx_hash)
+            .await?;
 
         // Update global state
         let mut root_info = LATEST_ROOT.write().await;
@@ -351,7 +513,8 @@ async fn process_log(pool: &PgPool, log: &alloy::rpc::types::Log) -> anyhow::Res
             tree.root()
         };
 
-        if our_root != event.root {
+        let computed_root_matches = our_root == event.root;
+        if !computed_root_matches {
             tracing::warn!(
                 contract_root = %event.root,
                 computed_root = %our_root,
@@ -363,46 +526,44 @@ async fn process_log(pool: &PgPool, log: &alloy::rpc::types::Log) -> anyhow::Res
                 epoch = event.rootEpoch,
                 "Root recorded and verified"
             );
-        }
-    }
 
-    Ok(())
-}
-
-async fn load_checkpoint(pool: &PgPool) -> anyhow::Result<Option<u64>> {
-    let row: Option<(i64,)> = sqlx::query_as(
-        "SELECT last_block FROM checkpoints WHERE name = 'indexer' LIMIT 1"
-    )
-    .fetch_optional(pool)
-    .await?;
-
-    Ok(row.map(|(block,)| block as u64))
-}
+            // Only retain snapshots of roots we actually verified - a mismatched tree isn't
+            // something we want callers generating "valid" proofs against.
+            let snapshot = {
+                let tree = GLOBAL_TREE.read().await;
+                tree.derived()
+            };
+            let mut history = TREE_HISTORY.write().await;
+            history.push_front((event.rootEpoch, snapshot));
+            while history.len() > root_history {
+                history.pop_back();
+            }
+        }
 
-async fn save_checkpoint(pool: &PgPool, block: u64) -> anyhow::Result<()> {
-    sqlx::query(
-        r#"INSERT INTO checkpoints (name, last_block)
-           VALUES ('indexer', $1)
-           ON CONFLICT (name) DO UPDATE
-           SET last_block = EXCLUDED.last_block"#
-    )
-    .bind(block as i64)
-    .execute(pool)
-    .await?;
+        // Best-effort: no subscribers just means `send` returns an error we ignore.
+        let _ = ROOT_NOTIFICATIONS.send(RootNotification {
+            root: event.root,
+            timestamp: event.timestamp,
+            epoch: event.rootEpoch,
+            block_number,
+            computed_root_matches,
+        });
+    }
 
     Ok(())
 }
 
 // HTTP API endpoints
-async fn start_http_server(addr: SocketAddr, pool: PgPool) -> anyhow::Result<()> {
+async fn start_http_server<S: AccountStore>(addr: SocketAddr, store: S) -> anyhow::Result<()> {
     let app = axum::Router::new()
         .route("/health", axum::routing::get(health))
         .route("/latest-root", axum::routing::get(get_latest_root))
-        .route("/account/:index", axum::routing::get(get_account))
-        .route("/proof/:index", axum::routing::get(get_inclusion_proof))
-        .route("/roots", axum::routing::get(get_roots))
-        .route("/stats", axum::routing::get(get_stats))
-        .with_state(pool);
+        .route("/subscribe/roots", axum::routing::get(subscribe_roots))
+        .route("/account/:index", axum::routing::get(get_account::<S>))
+        .route("/proof/:index", axum::routing::get(get_inclusion_proof::<S>))
+        .route("/roots", axum::routing::get(get_roots::<S>))
+        .route("/stats", axum::routing::get(get_stats::<S>))
+        .with_state(store);
 
     tracing::info!(%addr, "HTTP server listening");
     axum::serve(tokio::net::TcpListener::bind(addr).await?, app).await?;
@@ -430,10 +591,43 @@ async fn get_latest_root() -> impl IntoResponse {
     }))
 }
 
+// Streams newly recorded roots as they land on-chain, so downstream provers/relayers don't
+// have to busy-poll `/latest-root`. A late subscriber immediately gets the current latest
+// root on connect, then every subsequent `RootRecorded` as it's processed.
+async fn subscribe_roots() -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let rx = ROOT_NOTIFICATIONS.subscribe();
+
+    let current = {
+        let root_info = LATEST_ROOT.read().await;
+        let tree = GLOBAL_TREE.read().await;
+        RootNotification {
+            root: root_info.root,
+            timestamp: root_info.timestamp,
+            epoch: root_info.epoch,
+            block_number: root_info.block_number,
+            computed_root_matches: root_info.root == tree.root(),
+        }
+    };
+
+    let initial = stream::once(async move { Ok(root_notification_event(&current)) });
+    let updates = BroadcastStream::new(rx).filter_map(|notification| async move {
+        notification.ok().map(|n| Ok(root_notification_event(&n)))
+    });
+
+    Sse::new(initial.chain(updates)).keep_alive(KeepAlive::default())
+}
+
 // Generate an inclusion proof for a particular account
-async fn get_inclusion_proof(
+#[derive(Debug, serde::Deserialize)]
+struct ProofQuery {
+    epoch: Option<u64>,
+    root: Option<String>,
+}
+
+async fn get_inclusion_proof<S: AccountStore>(
     Path(index): Path<String>,
-    State(pool): State<PgPool>,
+    Query(query): Query<ProofQuery>,
+    State(store): State<S>,
 ) -> impl IntoResponse {
     let account_index: U256 = match index.parse() {
         Ok(idx) => idx,
@@ -456,37 +650,35 @@ async fn get_inclusion_proof(
         ).into_response();
     }
 
+    let requested_root: Option<U256> = match query.root.as_deref().map(|s| s.parse::<U256>()) {
+        Some(Ok(root)) => Some(root),
+        Some(Err(_)) => {
+            return (
+                axum::http::StatusCode::BAD_REQUEST,
+                axum::Json(serde_json::json!({
+                    "error": "Invalid root"
+                }))
+            ).into_response();
+        }
+        None => None,
+    };
+
     // Get account data from database
-    let row: Option<(String,)> = sqlx::query_as(
-        "SELECT identity_commitment FROM accounts WHERE account_index = $1"
-    )
-    .bind(account_index.to_string())
-    .fetch_optional(&pool)
-    .await
-    .ok()
-    .flatten();
-
-    if row.is_none() {
+    // TODO: this is always the *current* commitment - if it's since been updated, a proof
+    // against an older epoch's tree won't actually validate against it. Good enough for now.
+    let record = store.get_account(account_index).await.ok().flatten();
+
+    let Some(record) = record else {
         return (
             axum::http::StatusCode::NOT_FOUND,
             axum::Json(serde_json::json!({
                 "error": "Account not found"
             }))
         ).into_response();
-    }
-
-    let identity_commitment: U256 = match row.unwrap().0.parse() {
-        Ok(c) => c,
-        Err(_) => {
-            return (
-                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
-                axum::Json(serde_json::json!({
-                    "error": "Invalid commitment in database"
-                }))
-            ).into_response();
-        }
     };
 
+    let identity_commitment = record.identity_commitment;
+
     let tree_index = account_index.as_limbs()[0] as usize - 1;
 
     if tree_index >= (1usize << TREE_DEPTH) {
@@ -498,10 +690,43 @@ async fn get_inclusion_proof(
         ).into_response();
     }
 
-    // Generate proof from the merkle tree
-    let tree = GLOBAL_TREE.read().await;
-    let proof = tree.proof(tree_index);
-    let root = tree.root();
+    // Resolve which tree version to prove against: the live tree by default, or a retained
+    // historical snapshot when an epoch/root was requested.
+    let (proof, root) = if query.epoch.is_none() && requested_root.is_none() {
+        let tree = GLOBAL_TREE.read().await;
+        (tree.proof(tree_index), tree.root())
+    } else {
+        let history = TREE_HISTORY.read().await;
+
+        let found = if let Some(epoch) = query.epoch {
+            history.iter().find(|(e, _)| *e == epoch).map(|(_, tree)| tree)
+        } else {
+            history.iter().find(|(_, tree)| Some(tree.root()) == requested_root).map(|(_, tree)| tree)
+        };
+
+        let Some(tree) = found else {
+            let oldest_retained_epoch = history.back().map(|(epoch, _)| *epoch);
+            let is_too_old = matches!((query.epoch, oldest_retained_epoch), (Some(requested), Some(oldest)) if requested < oldest);
+
+            let status = if is_too_old {
+                axum::http::StatusCode::GONE
+            } else {
+                axum::http::StatusCode::NOT_FOUND
+            };
+            let message = if is_too_old {
+                "requested epoch is older than the retained root history window, re-sync required"
+            } else {
+                "no retained tree version matches the requested epoch/root"
+            };
+
+            return (
+                status,
+                axum::Json(serde_json::json!({ "error": message }))
+            ).into_response();
+        };
+
+        (tree.proof(tree_index), tree.root())
+    };
 
     // Convert proof to array of siblings
     let siblings: Vec<String> = proof.0.iter().map(|branch| {
@@ -547,9 +772,9 @@ fn verify_proof_internal(
     hash == *expected_root
 }
 
-async fn get_account(
+async fn get_account<S: AccountStore>(
     Path(index): Path<String>,
-    State(pool): State<PgPool>,
+    State(store): State<S>,
 ) -> impl IntoResponse {
     let account_index: U256 = match index.parse() {
         Ok(idx) => idx,
@@ -563,25 +788,15 @@ async fn get_account(
         }
     };
 
-    let row: Option<(String, i64, String, i64)> = sqlx::query_as(
-        r#"SELECT identity_commitment, block_number, tx_hash,
-           EXTRACT(EPOCH FROM created_at)::bigint as created_at
-           FROM accounts WHERE account_index = $1"#
-    )
-    .bind(account_index.to_string())
-    .fetch_optional(&pool)
-    .await
-    .ok()
-    .flatten();
-
-    match row {
-        Some((commitment, block, tx_hash, created_at)) => {
+    let record = store.get_account(account_index).await.ok().flatten();
+
+    match record {
+        Some(record) => {
             axum::Json(serde_json::json!({
                 "account_index": account_index.to_string(),
-                "identity_commitment": commitment,
-                "block_number": block,
-                "tx_hash": tx_hash,
-                "created_at": created_at
+                "identity_commitment": format!("0x{:064x}", record.identity_commitment),
+                "block_number": record.block_number,
+                "tx_hash": record.tx_hash
             })).into_response()
         }
         None => (
@@ -593,25 +808,17 @@ async fn get_account(
     }
 }
 
-async fn get_roots(State(pool): State<PgPool>) -> impl IntoResponse {
-    let rows: Vec<(String, i64, i64, i64)> = sqlx::query_as(
-        r#"SELECT root, timestamp, epoch, block_number
-           FROM roots
-           ORDER BY epoch DESC
-           LIMIT 100"#
-    )
-    .fetch_all(&pool)
-    .await
-    .unwrap_or_default();
-
-    let roots: Vec<serde_json::Value> = rows
+async fn get_roots<S: AccountStore>(State(store): State<S>) -> impl IntoResponse {
+    let roots = store.recent_roots(100).await.unwrap_or_default();
+
+    let roots: Vec<serde_json::Value> = roots
         .into_iter()
-        .map(|(root, timestamp, epoch, block)| {
+        .map(|record| {
             serde_json::json!({
-                "root": root,
-                "timestamp": timestamp,
-                "epoch": epoch,
-                "block_number": block
+                "root": format!("0x{:064x}", record.root),
+                "timestamp": record.timestamp,
+                "epoch": record.epoch,
+                "block_number": record.block_number
             })
         })
         .collect();
@@ -621,34 +828,67 @@ async fn get_roots(State(pool): State<PgPool>) -> impl IntoResponse {
     }))
 }
 
-async fn get_stats(State(pool): State<PgPool>) -> impl IntoResponse {
-    let total_accounts: Option<(i64,)> = sqlx::query_as(
-        "SELECT COUNT(*) FROM accounts"
-    )
-    .fetch_optional(&pool)
-    .await
-    .ok()
-    .flatten();
-
-    let total_updates: Option<(i64,)> = sqlx::query_as(
-        "SELECT COUNT(*) FROM account_updates"
-    )
-    .fetch_optional(&pool)
-    .await
-    .ok()
-    .flatten();
-
-    let total_roots: Option<(i64,)> = sqlx::query_as(
-        "SELECT COUNT(*) FROM roots"
-    )
-    .fetch_optional(&pool)
-    .await
-    .ok()
-    .flatten();
+async fn get_stats<S: AccountStore>(State(store): State<S>) -> impl IntoResponse {
+    let counts = store.counts().await.unwrap_or_default();
 
     axum::Json(serde_json::json!({
-        "total_accounts": total_accounts.map(|(c,)| c).unwrap_or(0),
-        "total_updates": total_updates.map(|(c,)| c).unwrap_or(0),
-        "total_roots": total_roots.map(|(c,)| c).unwrap_or(0)
+        "total_accounts": counts.accounts,
+        "total_updates": counts.updates,
+        "total_roots": counts.roots
     }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::EmbeddedStore;
+
+    // Regression test for the reorg-rollback bug the review caught: before
+    // `reset_cached_roots_after_rollback` existed, `check_for_reorg`'s rollback left
+    // `TREE_HISTORY`/`LATEST_ROOT` pointing at roots the rollback had just deleted from the
+    // abandoned fork, so `/proof/:index?epoch=...` kept serving proofs against stale history.
+    #[tokio::test]
+    async fn reset_cached_roots_after_rollback_drops_abandoned_fork_state() {
+        let path = std::env::temp_dir().join(format!("rp-indexer-test-{}", std::process::id()));
+        let store = EmbeddedStore::open(&path).expect("open embedded store");
+
+        // Seed the cached state as if epoch 1 had been recorded and verified pre-reorg.
+        store
+            .record_root(U256::from(111), 1_000, 1, 10, "0xabc")
+            .await
+            .expect("record root");
+        {
+            let mut history = TREE_HISTORY.write().await;
+            history.clear();
+            history.push_front((1, GLOBAL_TREE.read().await.derived()));
+        }
+        {
+            let mut root_info = LATEST_ROOT.write().await;
+            *root_info = RootInfo {
+                root: U256::from(111),
+                timestamp: 1_000,
+                epoch: 1,
+                block_number: 10,
+            };
+        }
+
+        // Simulate `rollback_to` having already dropped the abandoned fork's root (block 10
+        // rolled back to ancestor 5, so epoch 1's root at block 10 is gone).
+        store.rollback_to(5).await.expect("rollback");
+
+        reset_cached_roots_after_rollback(&store)
+            .await
+            .expect("reset cached roots after rollback");
+
+        assert!(
+            TREE_HISTORY.read().await.is_empty(),
+            "TREE_HISTORY must be cleared once its snapshots no longer match the canonical chain"
+        );
+        let root_info = LATEST_ROOT.read().await;
+        assert_eq!(root_info.epoch, 0);
+        assert_eq!(root_info.root, U256::ZERO);
+        drop(root_info);
+
+        std::fs::remove_dir_all(&path).ok();
+    }
+}
@@ -0,0 +1,270 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use alloy::primitives::U256;
+
+use super::{AccountRecord, AccountStore, RootRecord, StoreCounts};
+
+// No-Postgres backend: everything lives in a local `sled` database, so the indexer can run
+// as a single self-contained binary for operators who don't want to stand up Postgres.
+//
+// Keys are big-endian encoded so sled's byte-lexicographic iteration order doubles as
+// numeric order - `accounts` by account_index, `block_hashes` by block number, etc.
+#[derive(Clone)]
+pub struct EmbeddedStore {
+    db: sled::Db,
+    accounts: sled::Tree,
+    // keyed by block_number(8) || account_index(32) || a uniquifying counter(8), so updates
+    // to the same account in the same block don't clobber each other and stay time-ordered.
+    account_updates: sled::Tree,
+    // keyed by epoch(8), since that's what we page `recent_roots` by.
+    roots: sled::Tree,
+    block_hashes: sled::Tree,
+    meta: sled::Tree,
+}
+
+const CHECKPOINT_KEY: &[u8] = b"checkpoint";
+
+impl EmbeddedStore {
+    pub fn open(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let db = sled::open(path)?;
+        Ok(Self {
+            accounts: db.open_tree("accounts")?,
+            account_updates: db.open_tree("account_updates")?,
+            roots: db.open_tree("roots")?,
+            block_hashes: db.open_tree("block_hashes")?,
+            meta: db.open_tree("meta")?,
+            db,
+        })
+    }
+}
+
+fn encode_account(record: &AccountRecord) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(32 + 8 + 8 + record.tx_hash.len());
+    buf.extend_from_slice(&record.identity_commitment.to_be_bytes::<32>());
+    buf.extend_from_slice(&record.block_number.to_be_bytes());
+    buf.extend_from_slice(&record.added_block_number.to_be_bytes());
+    buf.extend_from_slice(record.tx_hash.as_bytes());
+    buf
+}
+
+fn decode_account(bytes: &[u8]) -> anyhow::Result<AccountRecord> {
+    anyhow::ensure!(bytes.len() >= 48, "corrupt account record");
+    Ok(AccountRecord {
+        identity_commitment: U256::from_be_slice(&bytes[0..32]),
+        block_number: u64::from_be_bytes(bytes[32..40].try_into()?),
+        added_block_number: u64::from_be_bytes(bytes[40..48].try_into()?),
+        tx_hash: String::from_utf8(bytes[48..].to_vec())?,
+    })
+}
+
+impl AccountStore for EmbeddedStore {
+    async fn insert_account(
+        &self,
+        account_index: U256,
+        identity_commitment: U256,
+        block_number: u64,
+        tx_hash: &str,
+    ) -> anyhow::Result<()> {
+        let key = account_index.to_be_bytes::<32>();
+        if self.accounts.contains_key(key)? {
+            return Ok(()); // matches the Postgres `ON CONFLICT ... DO NOTHING`
+        }
+        let record = AccountRecord {
+            identity_commitment,
+            block_number,
+            added_block_number: block_number,
+            tx_hash: tx_hash.to_string(),
+        };
+        self.accounts.insert(key, encode_account(&record))?;
+        Ok(())
+    }
+
+    async fn apply_update(
+        &self,
+        account_index: U256,
+        old_commitment: U256,
+        new_commitment: U256,
+        block_number: u64,
+        tx_hash: &str,
+    ) -> anyhow::Result<()> {
+        let key = account_index.to_be_bytes::<32>();
+        if let Some(existing) = self.accounts.get(key)? {
+            let mut record = decode_account(&existing)?;
+            record.identity_commitment = new_commitment;
+            record.block_number = block_number;
+            record.tx_hash = tx_hash.to_string();
+            self.accounts.insert(key, encode_account(&record))?;
+        }
+
+        let mut update_key = Vec::with_capacity(8 + 32 + 8);
+        update_key.extend_from_slice(&block_number.to_be_bytes());
+        update_key.extend_from_slice(&key);
+        update_key.extend_from_slice(&self.db.generate_id()?.to_be_bytes());
+
+        let mut update_value = Vec::with_capacity(64 + tx_hash.len());
+        update_value.extend_from_slice(&old_commitment.to_be_bytes::<32>());
+        update_value.extend_from_slice(&new_commitment.to_be_bytes::<32>());
+        update_value.extend_from_slice(tx_hash.as_bytes());
+        self.account_updates.insert(update_key, update_value)?;
+
+        Ok(())
+    }
+
+    async fn record_root(
+        &self,
+        root: U256,
+        timestamp: u64,
+        epoch: u64,
+        block_number: u64,
+        _tx_hash: &str,
+    ) -> anyhow::Result<()> {
+        let mut value = Vec::with_capacity(48);
+        value.extend_from_slice(&root.to_be_bytes::<32>());
+        value.extend_from_slice(&timestamp.to_be_bytes());
+        value.extend_from_slice(&block_number.to_be_bytes());
+        self.roots.insert(epoch.to_be_bytes(), value)?;
+        Ok(())
+    }
+
+    async fn get_account(&self, account_index: U256) -> anyhow::Result<Option<AccountRecord>> {
+        self.accounts
+            .get(account_index.to_be_bytes::<32>())?
+            .map(|bytes| decode_account(&bytes))
+            .transpose()
+    }
+
+    async fn iter_accounts_ordered(&self) -> anyhow::Result<Vec<(U256, U256)>> {
+        self.accounts
+            .iter()
+            .map(|entry| {
+                let (key, value) = entry?;
+                let account_index = U256::from_be_slice(&key);
+                let record = decode_account(&value)?;
+                Ok((account_index, record.identity_commitment))
+            })
+            .collect()
+    }
+
+    async fn recent_roots(&self, limit: u64) -> anyhow::Result<Vec<RootRecord>> {
+        self.roots
+            .iter()
+            .rev()
+            .take(limit as usize)
+            .map(|entry| {
+                let (key, value) = entry?;
+                anyhow::ensure!(value.len() >= 48, "corrupt root record");
+                Ok(RootRecord {
+                    root: U256::from_be_slice(&value[0..32]),
+                    timestamp: u64::from_be_bytes(value[32..40].try_into()?),
+                    epoch: u64::from_be_bytes(key.as_ref().try_into()?),
+                    block_number: u64::from_be_bytes(value[40..48].try_into()?),
+                })
+            })
+            .collect()
+    }
+
+    async fn counts(&self) -> anyhow::Result<StoreCounts> {
+        Ok(StoreCounts {
+            accounts: self.accounts.len() as u64,
+            updates: self.account_updates.len() as u64,
+            roots: self.roots.len() as u64,
+        })
+    }
+
+    async fn load_checkpoint(&self) -> anyhow::Result<Option<u64>> {
+        self.meta
+            .get(CHECKPOINT_KEY)?
+            .map(|bytes| Ok(u64::from_be_bytes(bytes.as_ref().try_into()?)))
+            .transpose()
+    }
+
+    async fn save_checkpoint(&self, block: u64) -> anyhow::Result<()> {
+        self.meta.insert(CHECKPOINT_KEY, &block.to_be_bytes())?;
+        Ok(())
+    }
+
+    async fn save_block_hash(&self, number: u64, hash: &str) -> anyhow::Result<()> {
+        self.block_hashes.insert(number.to_be_bytes(), hash.as_bytes())?;
+        Ok(())
+    }
+
+    async fn load_block_hash(&self, number: u64) -> anyhow::Result<Option<String>> {
+        self.block_hashes
+            .get(number.to_be_bytes())?
+            .map(|bytes| Ok(String::from_utf8(bytes.to_vec())?))
+            .transpose()
+    }
+
+    async fn rollback_to(&self, ancestor: u64) -> anyhow::Result<()> {
+        // Accounts added after the ancestor never existed on the canonical chain.
+        for entry in self.accounts.iter() {
+            let (key, value) = entry?;
+            let record = decode_account(&value)?;
+            if record.added_block_number > ancestor {
+                self.accounts.remove(&key)?;
+            }
+        }
+
+        // Earliest (lowest block_number) pre-reorg commitment per account, so accounts
+        // merely updated after the ancestor get their prior commitment restored.
+        let mut earliest_old_commitment: HashMap<[u8; 32], U256> = HashMap::new();
+        let range_start = (ancestor + 1).to_be_bytes().to_vec();
+        for entry in self.account_updates.range(range_start..) {
+            let (key, value) = entry?;
+            anyhow::ensure!(key.len() == 48, "corrupt account_updates key");
+            let account_index: [u8; 32] = key[8..40].try_into()?;
+            let old_commitment = U256::from_be_slice(&value[0..32]);
+            earliest_old_commitment
+                .entry(account_index)
+                .or_insert(old_commitment);
+        }
+
+        for (account_index, old_commitment) in earliest_old_commitment {
+            if let Some(existing) = self.accounts.get(account_index)? {
+                let mut record = decode_account(&existing)?;
+                if record.block_number > ancestor {
+                    record.identity_commitment = old_commitment;
+                    record.block_number = ancestor;
+                    self.accounts.insert(account_index, encode_account(&record))?;
+                }
+            }
+        }
+
+        let stale_updates: Vec<_> = self
+            .account_updates
+            .range(range_start..)
+            .map(|entry| entry.map(|(key, _)| key))
+            .collect::<Result<_, _>>()?;
+        for key in stale_updates {
+            self.account_updates.remove(&key)?;
+        }
+
+        let stale_roots: Vec<_> = self
+            .roots
+            .iter()
+            .filter_map(|entry| match entry {
+                Ok((key, value)) if value.len() >= 48 => {
+                    let block_number = u64::from_be_bytes(value[40..48].try_into().ok()?);
+                    (block_number > ancestor).then_some(Ok(key))
+                }
+                Ok(_) => None,
+                Err(err) => Some(Err(err)),
+            })
+            .collect::<Result<_, _>>()?;
+        for key in stale_roots {
+            self.roots.remove(&key)?;
+        }
+
+        let stale_hashes: Vec<_> = self
+            .block_hashes
+            .range(range_start..)
+            .map(|entry| entry.map(|(key, _)| key))
+            .collect::<Result<_, _>>()?;
+        for key in stale_hashes {
+            self.block_hashes.remove(&key)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,94 @@
+use std::future::Future;
+
+use alloy::primitives::U256;
+
+mod embedded;
+mod postgres;
+
+pub use embedded::EmbeddedStore;
+pub use postgres::PostgresStore;
+
+#[derive(Debug, Clone)]
+pub struct AccountRecord {
+    pub identity_commitment: U256,
+    pub block_number: u64,
+    pub added_block_number: u64,
+    pub tx_hash: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct RootRecord {
+    pub root: U256,
+    pub timestamp: u64,
+    pub epoch: u64,
+    pub block_number: u64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct StoreCounts {
+    pub accounts: u64,
+    pub updates: u64,
+    pub roots: u64,
+}
+
+// Every operation the indexer/HTTP layer actually needs, behind a trait so the Postgres
+// backend (the historical default) and the embedded backend (no external DB required) can
+// be swapped in from `Config` without touching `rp_indexer.rs`'s business logic.
+//
+// We return `impl Future<...> + Send` instead of writing these as `async fn` so the trait
+// stays free of the auto-trait-bound footguns `async fn` in traits has today.
+pub trait AccountStore: Clone + Send + Sync + 'static {
+    fn insert_account(
+        &self,
+        account_index: U256,
+        identity_commitment: U256,
+        block_number: u64,
+        tx_hash: &str,
+    ) -> impl Future<Output = anyhow::Result<()>> + Send;
+
+    fn apply_update(
+        &self,
+        account_index: U256,
+        old_commitment: U256,
+        new_commitment: U256,
+        block_number: u64,
+        tx_hash: &str,
+    ) -> impl Future<Output = anyhow::Result<()>> + Send;
+
+    fn record_root(
+        &self,
+        root: U256,
+        timestamp: u64,
+        epoch: u64,
+        block_number: u64,
+        tx_hash: &str,
+    ) -> impl Future<Output = anyhow::Result<()>> + Send;
+
+    fn get_account(
+        &self,
+        account_index: U256,
+    ) -> impl Future<Output = anyhow::Result<Option<AccountRecord>>> + Send;
+
+    // Ordered by account_index, for rebuilding the merkle tree from scratch.
+    fn iter_accounts_ordered(
+        &self,
+    ) -> impl Future<Output = anyhow::Result<Vec<(U256, U256)>>> + Send;
+
+    fn recent_roots(&self, limit: u64) -> impl Future<Output = anyhow::Result<Vec<RootRecord>>> + Send;
+
+    fn counts(&self) -> impl Future<Output = anyhow::Result<StoreCounts>> + Send;
+
+    fn load_checkpoint(&self) -> impl Future<Output = anyhow::Result<Option<u64>>> + Send;
+
+    fn save_checkpoint(&self, block: u64) -> impl Future<Output = anyhow::Result<()>> + Send;
+
+    fn save_block_hash(&self, number: u64, hash: &str) -> impl Future<Output = anyhow::Result<()>> + Send;
+
+    fn load_block_hash(&self, number: u64) -> impl Future<Output = anyhow::Result<Option<String>>> + Send;
+
+    // Roll everything recorded after `ancestor` back: drop accounts added after it, restore
+    // the pre-reorg commitment for accounts merely updated after it, and drop updates/roots/
+    // block hashes recorded after it. Implemented per-backend since the exact bookkeeping
+    // (SQL transaction vs. KV batch) differs.
+    fn rollback_to(&self, ancestor: u64) -> impl Future<Output = anyhow::Result<()>> + Send;
+}
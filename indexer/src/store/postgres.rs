@@ -0,0 +1,298 @@
+use alloy::primitives::U256;
+use sqlx::postgres::PgPoolOptions;
+use sqlx::{PgPool, Row};
+
+use super::{AccountRecord, AccountStore, RootRecord, StoreCounts};
+
+// The original backend: everything lives in Postgres, migrated with `sqlx::migrate!`.
+#[derive(Debug, Clone)]
+pub struct PostgresStore {
+    pool: PgPool,
+}
+
+impl PostgresStore {
+    pub async fn connect(db_url: &str) -> anyhow::Result<Self> {
+        let pool = PgPoolOptions::new()
+            .max_connections(10) //TODO: No idea here perhaps a config value..?
+            .connect(db_url)
+            .await?;
+
+        sqlx::migrate!("./migrations").run(&pool).await?;
+
+        Ok(Self { pool })
+    }
+
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+}
+
+impl AccountStore for PostgresStore {
+    async fn insert_account(
+        &self,
+        account_index: U256,
+        identity_commitment: U256,
+        block_number: u64,
+        tx_hash: &str,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"INSERT INTO accounts (account_index, identity_commitment, block_number, added_block_number, tx_hash)
+               VALUES ($1, $2, $3, $3, $4)
+               ON CONFLICT (account_index) DO NOTHING"#
+        )
+        .bind(account_index.to_string())
+        .bind(identity_commitment.to_string())
+        .bind(block_number as i64)
+        .bind(tx_hash)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn apply_update(
+        &self,
+        account_index: U256,
+        old_commitment: U256,
+        new_commitment: U256,
+        block_number: u64,
+        tx_hash: &str,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"UPDATE accounts
+               SET identity_commitment = $2,
+                   block_number = $3,
+                   tx_hash = $4,
+                   updated_at = NOW()
+               WHERE account_index = $1"#
+        )
+        .bind(account_index.to_string())
+        .bind(new_commitment.to_string())
+        .bind(block_number as i64)
+        .bind(tx_hash)
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            r#"INSERT INTO account_updates
+               (account_index, old_commitment, new_commitment, block_number, tx_hash)
+               VALUES ($1, $2, $3, $4, $5)"#
+        )
+        .bind(account_index.to_string())
+        .bind(old_commitment.to_string())
+        .bind(new_commitment.to_string())
+        .bind(block_number as i64)
+        .bind(tx_hash)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn record_root(
+        &self,
+        root: U256,
+        timestamp: u64,
+        epoch: u64,
+        block_number: u64,
+        tx_hash: &str,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"INSERT INTO roots (root, timestamp, epoch, block_number, tx_hash)
+               VALUES ($1, $2, $3, $4, $5)"#
+        )
+        .bind(root.to_string())
+        .bind(timestamp as i64)
+        .bind(epoch as i64)
+        .bind(block_number as i64)
+        .bind(tx_hash)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_account(&self, account_index: U256) -> anyhow::Result<Option<AccountRecord>> {
+        let row: Option<(String, i64, i64, String)> = sqlx::query_as(
+            r#"SELECT identity_commitment, block_number, added_block_number, tx_hash
+               FROM accounts WHERE account_index = $1"#
+        )
+        .bind(account_index.to_string())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|(commitment, block_number, added_block_number, tx_hash)| {
+            Ok(AccountRecord {
+                identity_commitment: commitment.parse()?,
+                block_number: block_number as u64,
+                added_block_number: added_block_number as u64,
+                tx_hash,
+            })
+        })
+        .transpose()
+    }
+
+    async fn iter_accounts_ordered(&self) -> anyhow::Result<Vec<(U256, U256)>> {
+        let rows = sqlx::query(
+            "SELECT account_index, identity_commitment FROM accounts ORDER BY account_index ASC"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let account_index: String = row.get("account_index");
+                let commitment: String = row.get("identity_commitment");
+                Ok((account_index.parse()?, commitment.parse()?))
+            })
+            .collect()
+    }
+
+    async fn recent_roots(&self, limit: u64) -> anyhow::Result<Vec<RootRecord>> {
+        let rows: Vec<(String, i64, i64, i64)> = sqlx::query_as(
+            r#"SELECT root, timestamp, epoch, block_number
+               FROM roots
+               ORDER BY epoch DESC
+               LIMIT $1"#
+        )
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|(root, timestamp, epoch, block_number)| {
+                Ok(RootRecord {
+                    root: root.parse()?,
+                    timestamp: timestamp as u64,
+                    epoch: epoch as u64,
+                    block_number: block_number as u64,
+                })
+            })
+            .collect()
+    }
+
+    async fn counts(&self) -> anyhow::Result<StoreCounts> {
+        let accounts: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM accounts")
+            .fetch_one(&self.pool)
+            .await?;
+        let updates: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM account_updates")
+            .fetch_one(&self.pool)
+            .await?;
+        let roots: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM roots")
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(StoreCounts {
+            accounts: accounts.0 as u64,
+            updates: updates.0 as u64,
+            roots: roots.0 as u64,
+        })
+    }
+
+    async fn load_checkpoint(&self) -> anyhow::Result<Option<u64>> {
+        let row: Option<(i64,)> = sqlx::query_as(
+            "SELECT last_block FROM checkpoints WHERE name = 'indexer' LIMIT 1"
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|(block,)| block as u64))
+    }
+
+    async fn save_checkpoint(&self, block: u64) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"INSERT INTO checkpoints (name, last_block)
+               VALUES ('indexer', $1)
+               ON CONFLICT (name) DO UPDATE
+               SET last_block = EXCLUDED.last_block"#
+        )
+        .bind(block as i64)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn save_block_hash(&self, number: u64, hash: &str) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"INSERT INTO block_hashes (number, hash)
+               VALUES ($1, $2)
+               ON CONFLICT (number) DO UPDATE
+               SET hash = EXCLUDED.hash"#
+        )
+        .bind(number as i64)
+        .bind(hash)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn load_block_hash(&self, number: u64) -> anyhow::Result<Option<String>> {
+        let row: Option<(String,)> = sqlx::query_as(
+            "SELECT hash FROM block_hashes WHERE number = $1"
+        )
+        .bind(number as i64)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|(hash,)| hash))
+    }
+
+    async fn rollback_to(&self, ancestor: u64) -> anyhow::Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM accounts WHERE added_block_number > $1")
+            .bind(ancestor as i64)
+            .execute(&mut *tx)
+            .await?;
+
+        let stale_updates: Vec<(String, String)> = sqlx::query_as(
+            r#"SELECT account_index, old_commitment FROM account_updates
+               WHERE block_number > $1
+               ORDER BY block_number ASC"#
+        )
+        .bind(ancestor as i64)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mut earliest_old_commitment: std::collections::HashMap<String, String> =
+            std::collections::HashMap::new();
+        for (account_index, old_commitment) in stale_updates {
+            earliest_old_commitment
+                .entry(account_index)
+                .or_insert(old_commitment);
+        }
+
+        for (account_index, old_commitment) in earliest_old_commitment {
+            sqlx::query(
+                r#"UPDATE accounts
+                   SET identity_commitment = $2,
+                       block_number = $3
+                   WHERE account_index = $1"#
+            )
+            .bind(&account_index)
+            .bind(&old_commitment)
+            .bind(ancestor as i64)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        sqlx::query("DELETE FROM account_updates WHERE block_number > $1")
+            .bind(ancestor as i64)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM roots WHERE block_number > $1")
+            .bind(ancestor as i64)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM block_hashes WHERE number > $1")
+            .bind(ancestor as i64)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,379 @@
+//! Handshake-authenticated, encrypted transport for [`TcpNetwork`](crate::TcpNetwork).
+//!
+//! The plain `tcp_connect`/[`TcpSessions`](crate::TcpSessions) bootstrap exchanges a session id
+//! in cleartext and then hands the raw [`TcpStream`] straight to [`TcpNetwork`](crate::TcpNetwork)
+//! - anyone who can reach the listener can pose as a peer. [`AuthenticatedTcpNetwork`] runs a
+//! Secret-Handshake-style box stream on top of that same stream before any MPC traffic flows:
+//!
+//! 1. Each side generates an ephemeral X25519 keypair and sends its public key together with
+//!    `HMAC(network_key, ephemeral_pub)`, so a connection that doesn't hold the pre-shared
+//!    `network_key` is rejected before any identity is revealed.
+//! 2. Both sides run X25519 on the ephemeral keys to agree on a shared secret, then exchange
+//!    their long-term ed25519 public key and a signature over the two ephemeral public keys
+//!    (in a canonical, id-ordered concatenation so both sides sign the same transcript). Each
+//!    side verifies the signature and that the presented public key matches the one configured
+//!    for that peer's [`PartyID`] slot.
+//! 3. The shared secret is expanded with HKDF-SHA256 into two directional keys, one per
+//!    direction of the link, each backing its own [`ChaCha20Poly1305`] instance with a
+//!    monotonically increasing per-direction nonce counter. Every frame that fails to decrypt
+//!    under that counter is rejected rather than delivered.
+//!
+//! The resulting [`AuthenticatedTcpNetwork`] implements [`Network`] exactly like
+//! [`TcpNetwork`](crate::TcpNetwork), so callers can pick either transport without changing how
+//! they use it afterwards.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::sync::atomic::AtomicUsize;
+
+use chacha20poly1305::{ChaCha20Poly1305, KeyInit, Nonce, aead::Aead};
+use ed25519_dalek::{Signer as _, SigningKey, Verifier as _, VerifyingKey};
+use eyre::{Context as _, ContextCompat as _};
+use futures::{SinkExt as _, StreamExt as _};
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use mpc_core::protocols::rep3::id::PartyID;
+use mpc_net::{ConnectionStats, Network};
+use rand_core::OsRng;
+use sha2::Sha256;
+use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio_util::codec::{Framed, LengthDelimitedCodec};
+use tokio_util::sync::CancellationToken;
+use x25519_dalek::{EphemeralSecret, PublicKey as XPublicKey};
+
+/// This party's long-term identity, used to authenticate both ends of every
+/// [`AuthenticatedTcpNetwork`] link, plus the network-wide pre-shared secret that gates the
+/// handshake before either side's identity is revealed.
+#[derive(Clone)]
+pub struct PeerIdentity {
+    /// This party's static ed25519 signing key.
+    pub identity_key: SigningKey,
+    /// Pre-shared secret known to all three parties, used to authenticate the handshake's
+    /// ephemeral key exchange via HMAC before either side proves its long-term identity.
+    pub network_key: [u8; 32],
+    /// Expected static ed25519 public key of every other party, keyed by [`PartyID`] index.
+    pub peer_identity_keys: HashMap<usize, VerifyingKey>,
+}
+
+/// Derives the two directional HKDF keys for a link between `local_id` and `other`, returning
+/// `(send_key, recv_key)` from `local_id`'s point of view.
+///
+/// Both sides derive the same pair of keys from the shared secret, one per direction of the
+/// link; which one each side sends vs. receives with is decided by comparing party ids, so no
+/// extra coordination is needed to agree on the assignment.
+fn directional_keys(shared_secret: &[u8; 32], local_id: usize, other: usize) -> ([u8; 32], [u8; 32]) {
+    let hk = Hkdf::<Sha256>::new(None, shared_secret);
+    let mut lower_to_higher = [0u8; 32];
+    hk.expand(b"tcp-mpc-net lower-to-higher", &mut lower_to_higher)
+        .expect("32 bytes is a valid HKDF output length");
+    let mut higher_to_lower = [0u8; 32];
+    hk.expand(b"tcp-mpc-net higher-to-lower", &mut higher_to_lower)
+        .expect("32 bytes is a valid HKDF output length");
+
+    if local_id > other {
+        (higher_to_lower, lower_to_higher)
+    } else {
+        (lower_to_higher, higher_to_lower)
+    }
+}
+
+/// Concatenates the two ephemeral public keys in ascending-party-id order, so both sides sign
+/// and verify the exact same transcript regardless of who ran which half of the exchange.
+fn transcript(
+    local_id: usize,
+    local_ephemeral: &XPublicKey,
+    other: usize,
+    other_ephemeral: &XPublicKey,
+) -> [u8; 64] {
+    let mut out = [0u8; 64];
+    if local_id < other {
+        out[..32].copy_from_slice(local_ephemeral.as_bytes());
+        out[32..].copy_from_slice(other_ephemeral.as_bytes());
+    } else {
+        out[..32].copy_from_slice(other_ephemeral.as_bytes());
+        out[32..].copy_from_slice(local_ephemeral.as_bytes());
+    }
+    out
+}
+
+/// Runs the Secret-Handshake-style box stream described in the [module docs](self) over `stream`
+/// and returns the two directional ciphers to encrypt/decrypt frames with afterwards.
+async fn handshake(
+    stream: &mut TcpStream,
+    identity: &PeerIdentity,
+    local_id: usize,
+    other: usize,
+) -> eyre::Result<(ChaCha20Poly1305, ChaCha20Poly1305)> {
+    let expected_identity_key = identity
+        .peer_identity_keys
+        .get(&other)
+        .with_context(|| format!("no configured identity key for party {other}"))?;
+
+    let ephemeral_secret = EphemeralSecret::random_from_rng(OsRng);
+    let ephemeral_pub = XPublicKey::from(&ephemeral_secret);
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(&identity.network_key)
+        .expect("HMAC-SHA256 accepts a 32-byte key");
+    mac.update(ephemeral_pub.as_bytes());
+    let tag = mac.finalize().into_bytes();
+
+    let mut hello = [0u8; 64];
+    hello[..32].copy_from_slice(ephemeral_pub.as_bytes());
+    hello[32..].copy_from_slice(&tag);
+
+    let (mut read_half, mut write_half) = stream.split();
+    let mut their_hello = [0u8; 64];
+    tokio::try_join!(
+        async { write_half.write_all(&hello).await.context("sending handshake hello") },
+        async {
+            read_half
+                .read_exact(&mut their_hello)
+                .await
+                .context("reading handshake hello")
+        },
+    )?;
+
+    let (their_ephemeral_bytes, their_tag) = their_hello.split_at(32);
+    let mut mac = Hmac::<Sha256>::new_from_slice(&identity.network_key)
+        .expect("HMAC-SHA256 accepts a 32-byte key");
+    mac.update(their_ephemeral_bytes);
+    mac.verify_slice(their_tag)
+        .map_err(|_| eyre::eyre!("party {other} is not on this network: HMAC mismatch"))?;
+    let their_ephemeral = XPublicKey::from(
+        <[u8; 32]>::try_from(their_ephemeral_bytes).expect("checked length above"),
+    );
+
+    let shared_secret = ephemeral_secret.diffie_hellman(&their_ephemeral);
+    let transcript = transcript(local_id, &ephemeral_pub, other, &their_ephemeral);
+
+    let signature = identity.identity_key.sign(&transcript);
+    let mut identity_frame = [0u8; 32 + 64];
+    identity_frame[..32].copy_from_slice(identity.identity_key.verifying_key().as_bytes());
+    identity_frame[32..].copy_from_slice(&signature.to_bytes());
+
+    let mut their_identity_frame = [0u8; 32 + 64];
+    tokio::try_join!(
+        async {
+            write_half
+                .write_all(&identity_frame)
+                .await
+                .context("sending handshake identity")
+        },
+        async {
+            read_half
+                .read_exact(&mut their_identity_frame)
+                .await
+                .context("reading handshake identity")
+        },
+    )?;
+
+    let (their_identity_pub, their_signature) = their_identity_frame.split_at(32);
+    eyre::ensure!(
+        their_identity_pub == expected_identity_key.as_bytes(),
+        "party {other} presented an unexpected identity key during handshake"
+    );
+    let their_signature = ed25519_dalek::Signature::from_slice(their_signature)
+        .context("peer sent a malformed handshake signature")?;
+    expected_identity_key
+        .verify(&transcript, &their_signature)
+        .map_err(|_| eyre::eyre!("party {other} failed handshake signature verification"))?;
+
+    let (send_key, recv_key) = directional_keys(shared_secret.as_bytes(), local_id, other);
+    let send_cipher = ChaCha20Poly1305::new(chacha20poly1305::Key::from_slice(&send_key));
+    let recv_cipher = ChaCha20Poly1305::new(chacha20poly1305::Key::from_slice(&recv_key));
+    Ok((send_cipher, recv_cipher))
+}
+
+/// Encrypts `plaintext` with `cipher` under the next nonce from `counter`, rejecting the frame
+/// once the 64-bit counter would wrap rather than ever reusing a nonce.
+fn seal(cipher: &ChaCha20Poly1305, counter: &mut u64, plaintext: &[u8]) -> eyre::Result<Vec<u8>> {
+    let mut nonce = [0u8; 12];
+    nonce[4..].copy_from_slice(&counter.to_be_bytes());
+    *counter = counter
+        .checked_add(1)
+        .context("per-direction nonce counter exhausted")?;
+    cipher
+        .encrypt(Nonce::from_slice(&nonce), plaintext)
+        .map_err(|err| eyre::eyre!("failed to encrypt frame: {err}"))
+}
+
+/// Decrypts `ciphertext` with `cipher` under the next expected nonce from `counter`, rejecting
+/// the frame if authentication fails.
+fn open(cipher: &ChaCha20Poly1305, counter: &mut u64, ciphertext: &[u8]) -> eyre::Result<Vec<u8>> {
+    let mut nonce = [0u8; 12];
+    nonce[4..].copy_from_slice(&counter.to_be_bytes());
+    *counter = counter
+        .checked_add(1)
+        .context("per-direction nonce counter exhausted")?;
+    cipher
+        .decrypt(Nonce::from_slice(&nonce), ciphertext)
+        .map_err(|err| eyre::eyre!("failed to authenticate frame: {err}"))
+}
+
+/// A [`Network`] that runs the handshake described in the [module docs](self) over a pair of
+/// [`TcpStream`]s before use, then seals/opens every frame with the resulting per-direction
+/// [`ChaCha20Poly1305`] ciphers. See [`TcpNetwork`](crate::TcpNetwork) for the unauthenticated
+/// equivalent this mirrors.
+#[derive(Debug)]
+#[expect(clippy::complexity)]
+pub struct AuthenticatedTcpNetwork {
+    id: PartyID,
+    send: HashMap<usize, (mpsc::Sender<Vec<u8>>, AtomicUsize)>,
+    recv: HashMap<usize, (Mutex<mpsc::Receiver<eyre::Result<Vec<u8>>>>, AtomicUsize)>,
+}
+
+impl AuthenticatedTcpNetwork {
+    pub async fn new(
+        id: PartyID,
+        mut next_stream: TcpStream,
+        mut prev_stream: TcpStream,
+        cancellation_token: CancellationToken,
+        identity: PeerIdentity,
+    ) -> eyre::Result<Self> {
+        let local_id = usize::from(id);
+        let (next_send_cipher, next_recv_cipher) =
+            handshake(&mut next_stream, &identity, local_id, id.next().into()).await?;
+        let (prev_send_cipher, prev_recv_cipher) =
+            handshake(&mut prev_stream, &identity, local_id, id.prev().into()).await?;
+
+        let mut send = HashMap::new();
+        let mut recv = HashMap::new();
+
+        let (next_send_tx, next_recv_rx) = spawn_link(
+            next_stream,
+            next_send_cipher,
+            next_recv_cipher,
+            cancellation_token.clone(),
+        );
+        let (prev_send_tx, prev_recv_rx) = spawn_link(
+            prev_stream,
+            prev_send_cipher,
+            prev_recv_cipher,
+            cancellation_token,
+        );
+
+        send.insert(id.next().into(), (next_send_tx, AtomicUsize::default()));
+        send.insert(id.prev().into(), (prev_send_tx, AtomicUsize::default()));
+        recv.insert(
+            id.next().into(),
+            (Mutex::new(next_recv_rx), AtomicUsize::default()),
+        );
+        recv.insert(
+            id.prev().into(),
+            (Mutex::new(prev_recv_rx), AtomicUsize::default()),
+        );
+
+        Ok(Self { id, send, recv })
+    }
+}
+
+/// Spawns the writer/reader task pair for one authenticated link, sealing every outgoing frame
+/// and opening every incoming one under the per-direction nonce counters described in the
+/// [module docs](self).
+fn spawn_link(
+    stream: TcpStream,
+    send_cipher: ChaCha20Poly1305,
+    recv_cipher: ChaCha20Poly1305,
+    cancellation_token: CancellationToken,
+) -> (
+    mpsc::Sender<Vec<u8>>,
+    mpsc::Receiver<eyre::Result<Vec<u8>>>,
+) {
+    let codec = LengthDelimitedCodec::new();
+    let (mut sender, mut receiver) = Framed::new(stream, codec).split();
+
+    let (send_tx, mut send_rx) = mpsc::channel::<Vec<u8>>(32);
+    let (recv_tx, recv_rx) = mpsc::channel::<eyre::Result<Vec<u8>>>(32);
+
+    tokio::task::spawn(async move {
+        let mut send_counter = 0u64;
+        while let Some(data) = send_rx.recv().await {
+            let frame = match seal(&send_cipher, &mut send_counter, &data) {
+                Ok(frame) => frame,
+                Err(err) => {
+                    tracing::warn!("failed to seal outgoing frame: {err:?}");
+                    break;
+                }
+            };
+            if let Err(err) = sender.send(frame.into()).await {
+                tracing::warn!("failed to send data: {err:?}");
+                break;
+            }
+        }
+    });
+
+    let cancellation_token_clone = cancellation_token.clone();
+    tokio::task::spawn(async move {
+        let mut recv_counter = 0u64;
+        loop {
+            tokio::select! {
+                _ = cancellation_token_clone.cancelled() => {
+                    break;
+                }
+                msg = receiver.next() => {
+                    match msg {
+                        Some(Ok(data)) => {
+                            let opened = open(&recv_cipher, &mut recv_counter, &data);
+                            let is_err = opened.is_err();
+                            if recv_tx.send(opened).await.is_err() {
+                                tracing::warn!("recv receiver dropped");
+                                break;
+                            }
+                            if is_err {
+                                break;
+                            }
+                        }
+                        Some(Err(err)) => {
+                            let _ = recv_tx.send(Err(eyre::eyre!("tcp error: {err}"))).await;
+                            break;
+                        }
+                        None => break,
+                    }
+                }
+            }
+        }
+    });
+
+    (send_tx, recv_rx)
+}
+
+impl Network for AuthenticatedTcpNetwork {
+    fn id(&self) -> usize {
+        self.id.into()
+    }
+
+    fn send(&self, to: usize, data: &[u8]) -> eyre::Result<()> {
+        let (sender, sent_bytes) = self.send.get(&to).context("party id out-of-bounds")?;
+        sent_bytes.fetch_add(data.len(), std::sync::atomic::Ordering::Relaxed);
+        sender.blocking_send(data.to_vec())?;
+        Ok(())
+    }
+
+    fn recv(&self, from: usize) -> eyre::Result<Vec<u8>> {
+        let (receiver, recv_bytes) = self.recv.get(&from).context("party id out-of-bounds")?;
+        let data = receiver
+            .lock()
+            .expect("not poisoned")
+            .blocking_recv()
+            .context("receiver sender dropped")??;
+        recv_bytes.fetch_add(data.len(), std::sync::atomic::Ordering::Relaxed);
+        Ok(data)
+    }
+
+    fn get_connection_stats(&self) -> ConnectionStats {
+        let mut stats = std::collections::BTreeMap::new();
+        for (id, (_, sent_bytes)) in self.send.iter() {
+            let recv_bytes = &self.recv.get(id).expect("was in send so must be in recv").1;
+            stats.insert(
+                *id,
+                (
+                    sent_bytes.load(std::sync::atomic::Ordering::Relaxed),
+                    recv_bytes.load(std::sync::atomic::Ordering::Relaxed),
+                ),
+            );
+        }
+        ConnectionStats::new(self.id.into(), stats)
+    }
+}
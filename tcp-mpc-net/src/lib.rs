@@ -1,35 +1,327 @@
+use std::collections::VecDeque;
 use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use std::{collections::HashMap, sync::atomic::AtomicUsize};
 
-use eyre::ContextCompat as _;
+use eyre::{Context as _, ContextCompat as _};
 use futures::{SinkExt as _, StreamExt as _};
 use mpc_core::protocols::rep3::id::PartyID;
 use mpc_net::{ConnectionStats, Network};
 use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
-use tokio::sync::mpsc;
+use tokio::sync::{Notify, mpsc};
+use tokio::task::JoinHandle;
 use tokio::{net::TcpStream, sync::oneshot};
 use tokio_util::codec::{Framed, LengthDelimitedCodec};
 use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
+pub mod auth;
+
+/// Once cancellation fires, how long a receive task keeps waiting for the peer to close its
+/// write half (or send its last in-flight bytes) before giving up and tearing down anyway.
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Outgoing messages larger than this are split into chunks, so a high-priority message queued
+/// behind a big one can be interleaved between its chunks instead of waiting for all of it to go
+/// out first. Each chunk is its own `LengthDelimitedCodec` frame.
+const CHUNK_SIZE: usize = 16 * 1024;
+
+/// How urgently a queued [`TcpNetwork::send_with_priority`] message should be delivered relative
+/// to other messages queued for the same peer. Within one priority level, messages interleave
+/// chunk-by-chunk in the order they were queued; [`Network::send`] uses [`Priority::Normal`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    Low,
+    #[default]
+    Normal,
+    High,
+}
+
+/// One message queued for a peer, not yet (fully) chunked onto the wire.
+#[derive(Debug)]
+struct PendingMessage {
+    request_id: u64,
+    data: Vec<u8>,
+    offset: usize,
+}
+
+impl PendingMessage {
+    /// Carves the next `CHUNK_SIZE` (or fewer, for the last one) bytes off this message.
+    fn next_chunk(&mut self) -> (u64, bool, Vec<u8>) {
+        let end = (self.offset + CHUNK_SIZE).min(self.data.len());
+        let chunk = self.data[self.offset..end].to_vec();
+        self.offset = end;
+        let more_follows = self.offset < self.data.len();
+        (self.request_id, more_follows, chunk)
+    }
+
+    fn is_done(&self) -> bool {
+        self.offset >= self.data.len()
+    }
+}
+
+/// Per-priority FIFOs of messages queued for one peer direction.
+#[derive(Debug, Default)]
+struct SendQueueBuckets {
+    high: VecDeque<PendingMessage>,
+    normal: VecDeque<PendingMessage>,
+    low: VecDeque<PendingMessage>,
+    closed: bool,
+}
+
+impl SendQueueBuckets {
+    fn bucket_mut(&mut self, priority: Priority) -> &mut VecDeque<PendingMessage> {
+        match priority {
+            Priority::High => &mut self.high,
+            Priority::Normal => &mut self.normal,
+            Priority::Low => &mut self.low,
+        }
+    }
+
+    /// Pops one chunk to send next, preferring higher-priority buckets over lower ones, and
+    /// round-robining within a bucket so several in-flight messages at the same priority still
+    /// interleave with each other.
+    fn pop_chunk(&mut self) -> Option<(u64, bool, Vec<u8>)> {
+        for bucket in [&mut self.high, &mut self.normal, &mut self.low] {
+            if let Some(mut message) = bucket.pop_front() {
+                let chunk = message.next_chunk();
+                if !message.is_done() {
+                    bucket.push_back(message);
+                }
+                return Some(chunk);
+            }
+        }
+        None
+    }
+}
+
+/// Priority scheduler for one peer direction's outgoing messages, following netapp's `SendQueue`
+/// design: [`Network::send`]/[`TcpNetwork::send_with_priority`] just enqueue here, and the
+/// writer task spawned in [`TcpNetwork::new`] pulls one chunk at a time via [`Self::next_chunk`],
+/// so a queued high-priority message is never stuck behind a large low-priority one.
+#[derive(Debug)]
+struct SendQueue {
+    next_request_id: AtomicU64,
+    buckets: Mutex<SendQueueBuckets>,
+    notify: Notify,
+}
+
+impl SendQueue {
+    fn new() -> Self {
+        Self {
+            next_request_id: AtomicU64::new(0),
+            buckets: Mutex::new(SendQueueBuckets::default()),
+            notify: Notify::new(),
+        }
+    }
+
+    fn push(&self, priority: Priority, data: Vec<u8>) {
+        let request_id = self.next_request_id.fetch_add(1, Ordering::Relaxed);
+        self.buckets
+            .lock()
+            .expect("not poisoned")
+            .bucket_mut(priority)
+            .push_back(PendingMessage {
+                request_id,
+                data,
+                offset: 0,
+            });
+        self.notify.notify_one();
+    }
+
+    /// Marks the queue as closed: once drained, [`Self::next_chunk`] resolves `None` instead of
+    /// waiting for more messages.
+    fn close(&self) {
+        self.buckets.lock().expect("not poisoned").closed = true;
+        self.notify.notify_one();
+    }
+
+    /// Waits for and pops the next chunk to send. Resolves to `None` once the queue is closed and
+    /// drained.
+    async fn next_chunk(&self) -> Option<(u64, bool, Vec<u8>)> {
+        loop {
+            let notified = self.notify.notified();
+            let (chunk, closed_and_empty) = {
+                let mut buckets = self.buckets.lock().expect("not poisoned");
+                match buckets.pop_chunk() {
+                    Some(chunk) => (Some(chunk), false),
+                    None => (None, buckets.closed),
+                }
+            };
+            match chunk {
+                Some(chunk) => return Some(chunk),
+                None if closed_and_empty => return None,
+                None => notified.await,
+            }
+        }
+    }
+}
+
+/// A decoded wire frame - either a chunk of message data or an acknowledgement of chunks already
+/// received, see [`encode_data_frame`]/[`encode_ack_frame`].
+#[derive(Debug)]
+enum DecodedFrame<'a> {
+    Data {
+        seq: u64,
+        request_id: u64,
+        more_follows: bool,
+        payload: &'a [u8],
+    },
+    /// The peer has received every frame up to and including `ack_seq` - see [`ResendBuffer`].
+    Ack { ack_seq: u64 },
+}
+
+/// Encodes a chunk's `seq`/`request_id`/`more_follows` header in front of its payload, ready to
+/// hand to the `LengthDelimitedCodec` sink as one frame. `seq` is this link's own monotonically
+/// increasing frame counter (for acking/replay), distinct from `request_id` (which identifies
+/// which queued message a chunk belongs to, for reassembly).
+fn encode_data_frame(seq: u64, request_id: u64, more_follows: bool, payload: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(18 + payload.len());
+    frame.push(0); // tag: data
+    frame.extend_from_slice(&seq.to_be_bytes());
+    frame.extend_from_slice(&request_id.to_be_bytes());
+    frame.push(more_follows as u8);
+    frame.extend_from_slice(payload);
+    frame
+}
+
+/// Encodes an acknowledgement of every frame up to and including `ack_seq`.
+fn encode_ack_frame(ack_seq: u64) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(9);
+    frame.push(1); // tag: ack
+    frame.extend_from_slice(&ack_seq.to_be_bytes());
+    frame
+}
+
+/// Decodes a wire frame produced by [`encode_data_frame`] or [`encode_ack_frame`].
+fn decode_frame(frame: &[u8]) -> eyre::Result<DecodedFrame<'_>> {
+    eyre::ensure!(!frame.is_empty(), "empty frame");
+    let (tag, rest) = frame.split_at(1);
+    match tag[0] {
+        0 => {
+            eyre::ensure!(rest.len() >= 17, "data frame too short");
+            let (header, payload) = rest.split_at(17);
+            let seq = u64::from_be_bytes(header[..8].try_into().expect("checked length above"));
+            let request_id =
+                u64::from_be_bytes(header[8..16].try_into().expect("checked length above"));
+            let more_follows = header[16] != 0;
+            Ok(DecodedFrame::Data {
+                seq,
+                request_id,
+                more_follows,
+                payload,
+            })
+        }
+        1 => {
+            eyre::ensure!(rest.len() == 8, "ack frame has the wrong length");
+            let ack_seq = u64::from_be_bytes(rest.try_into().expect("checked length above"));
+            Ok(DecodedFrame::Ack { ack_seq })
+        }
+        tag => eyre::bail!("unknown frame tag {tag}"),
+    }
+}
+
+/// Frames a [`TcpNetwork`] link has sent but the peer hasn't acked yet, retained so they can be
+/// replayed verbatim after a reconnect instead of being silently lost.
+#[derive(Debug, Default)]
+struct ResendBuffer {
+    next_seq: AtomicU64,
+    unacked: Mutex<VecDeque<(u64, Vec<u8>)>>,
+}
+
+impl ResendBuffer {
+    /// Sequence numbers start at 1, so a `last_received_seq` of 0 means nothing received yet.
+    fn next_seq(&self) -> u64 {
+        self.next_seq.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    fn record(&self, seq: u64, encoded_frame: Vec<u8>) {
+        self.unacked
+            .lock()
+            .expect("not poisoned")
+            .push_back((seq, encoded_frame));
+    }
+
+    /// Drops every frame the peer has confirmed receiving, up to and including `ack_seq`.
+    fn ack(&self, ack_seq: u64) {
+        self.unacked
+            .lock()
+            .expect("not poisoned")
+            .retain(|(seq, _)| *seq > ack_seq);
+    }
+
+    /// Snapshots the still-unacked frames, oldest first, for replay after a reconnect.
+    fn snapshot(&self) -> Vec<Vec<u8>> {
+        self.unacked
+            .lock()
+            .expect("not poisoned")
+            .iter()
+            .map(|(_, frame)| frame.clone())
+            .collect()
+    }
+}
+
+/// Reassembles chunked frames for one peer direction back into whole messages, keyed by the
+/// `request_id` each chunk was tagged with.
+#[derive(Debug, Default)]
+struct ChunkReassembly {
+    partial: HashMap<u64, Vec<u8>>,
+}
+
+impl ChunkReassembly {
+    /// Feeds in one decoded chunk, returning the whole message once its last chunk arrives.
+    fn push(&mut self, request_id: u64, more_follows: bool, payload: &[u8]) -> Option<Vec<u8>> {
+        let buf = self.partial.entry(request_id).or_default();
+        buf.extend_from_slice(payload);
+        if more_follows {
+            None
+        } else {
+            self.partial.remove(&request_id)
+        }
+    }
+}
+
 pub enum TcpSession {
-    Ready(TcpStream),
-    Waiter(oneshot::Sender<TcpStream>),
+    Ready(TcpStream, Instant),
+    Waiter(oneshot::Sender<TcpStream>, Instant),
+}
+
+/// Tunables for the reaper task [`TcpSessions::new`] spawns to keep its session table bounded -
+/// see [`TcpSessions`].
+#[derive(Debug, Clone, Copy)]
+pub struct TcpSessionsConfig {
+    /// How long a `Ready` stream may sit uncollected before the reaper closes and drops it.
+    pub session_ttl: Duration,
+    /// How long a `Waiter` may wait for its peer to connect before the reaper fails it.
+    pub waiter_timeout: Duration,
+    /// How often the reaper sweeps the session table for expired entries.
+    pub reap_interval: Duration,
+}
+
+impl Default for TcpSessionsConfig {
+    fn default() -> Self {
+        Self {
+            session_ttl: Duration::from_secs(60),
+            waiter_timeout: Duration::from_secs(60),
+            reap_interval: Duration::from_secs(10),
+        }
+    }
 }
 
 #[derive(Clone)]
 pub struct TcpSessions {
-    // TODO if streams are not collected from this map, they pile up and never get removed
     sessions: Arc<tokio::sync::Mutex<HashMap<Uuid, TcpSession>>>,
 }
 
 impl TcpSessions {
-    pub async fn new(bind_addr: SocketAddr) -> eyre::Result<Self> {
+    pub async fn new(bind_addr: SocketAddr, config: TcpSessionsConfig) -> eyre::Result<Self> {
         let listener = tokio::net::TcpListener::bind(bind_addr).await?;
         let sessions = Self {
             sessions: Arc::default(),
         };
+
         let sessions_clone = sessions.clone();
         tokio::spawn(async move {
             loop {
@@ -43,22 +335,63 @@ impl TcpSessions {
             #[allow(unreachable_code)]
             eyre::Ok(())
         });
+
+        let sessions_clone = sessions.clone();
+        tokio::spawn(async move {
+            sessions_clone.reap(config).await;
+        });
+
         Ok(sessions)
     }
 
+    /// Periodically evicts `Ready` streams older than `config.session_ttl` and fails `Waiter`s
+    /// that have been pending past `config.waiter_timeout`, so a peer that never shows up (or
+    /// never gets collected) doesn't leak a slot in `sessions` forever.
+    async fn reap(&self, config: TcpSessionsConfig) {
+        let mut interval = tokio::time::interval(config.reap_interval);
+        loop {
+            interval.tick().await;
+            let mut sessions = self.sessions.lock().await;
+            sessions.retain(|session_id, session| match session {
+                TcpSession::Ready(_, inserted_at) => {
+                    let expired = inserted_at.elapsed() > config.session_ttl;
+                    if expired {
+                        tracing::warn!(
+                            "reaping uncollected tcp session {session_id} after {:?}",
+                            inserted_at.elapsed()
+                        );
+                    }
+                    !expired
+                }
+                TcpSession::Waiter(_, registered_at) => {
+                    let expired = registered_at.elapsed() > config.waiter_timeout;
+                    if expired {
+                        tracing::warn!(
+                            "timing out waiter for tcp session {session_id} after {:?}",
+                            registered_at.elapsed()
+                        );
+                        // Dropping the sender wakes the waiting `get` with a recv error instead
+                        // of leaving it pending forever.
+                    }
+                    !expired
+                }
+            });
+        }
+    }
+
     pub async fn get(&self, session_id: Uuid) -> eyre::Result<TcpStream> {
         let mut sessions = self.sessions.lock().await;
         let session = sessions.remove(&session_id);
         match session {
-            Some(TcpSession::Ready(stream)) => Ok(stream),
-            Some(TcpSession::Waiter(_)) => {
+            Some(TcpSession::Ready(stream, _)) => Ok(stream),
+            Some(TcpSession::Waiter(..)) => {
                 eyre::bail!("tried to get same session twice")
             }
             None => {
                 let (tx, rx) = oneshot::channel();
-                sessions.insert(session_id, TcpSession::Waiter(tx));
+                sessions.insert(session_id, TcpSession::Waiter(tx, Instant::now()));
                 drop(sessions); // drop to release lock
-                Ok(rx.await?)
+                rx.await.context("timed out waiting for peer to connect")
             }
         }
     }
@@ -67,14 +400,14 @@ impl TcpSessions {
         let mut sessions = self.sessions.lock().await;
         let session = sessions.remove(&session_id);
         match session {
-            Some(TcpSession::Ready(_)) => {
+            Some(TcpSession::Ready(..)) => {
                 eyre::bail!("tried to insert same session twice")
             }
-            Some(TcpSession::Waiter(tx)) => {
+            Some(TcpSession::Waiter(tx, _)) => {
                 let _ = tx.send(stream);
             }
             None => {
-                sessions.insert(session_id, TcpSession::Ready(stream));
+                sessions.insert(session_id, TcpSession::Ready(stream, Instant::now()));
             }
         }
         Ok(())
@@ -93,107 +426,257 @@ pub async fn tcp_connect(addr: SocketAddr, session_id: Uuid) -> eyre::Result<Tcp
     Ok(stream)
 }
 
+/// How a [`TcpNetwork`] link re-establishes its connection after the peer drops it - see
+/// [`ReconnectConfig`] and the `redial`/`redial_with_retry` helpers.
+#[derive(Clone)]
+pub enum RedialStrategy {
+    /// Re-dial the peer at `addr`, presenting the same `session_id` so the reconnect is
+    /// recognized as the continuation of an existing link rather than a brand new one.
+    Connect { addr: SocketAddr, session_id: Uuid },
+    /// Wait for the peer to re-dial us and claim `session_id` from `sessions`.
+    Accept {
+        sessions: TcpSessions,
+        session_id: Uuid,
+    },
+}
+
+impl RedialStrategy {
+    async fn redial(&self) -> eyre::Result<TcpStream> {
+        match self {
+            RedialStrategy::Connect { addr, session_id } => tcp_connect(*addr, *session_id).await,
+            RedialStrategy::Accept {
+                sessions,
+                session_id,
+            } => sessions.get(*session_id).await,
+        }
+    }
+}
+
+/// Retry budget for reconnecting a [`TcpNetwork`] link after it drops - see
+/// [`RedialStrategy`]/[`TcpNetwork::new`].
+#[derive(Debug, Clone, Copy)]
+pub struct ReconnectConfig {
+    /// How many times a failed redial is retried (so up to `max_retries + 1` attempts in all)
+    /// before giving up and surfacing the connection as dead.
+    pub max_retries: u32,
+    /// How long to wait between redial attempts.
+    pub retry_backoff: Duration,
+}
+
+impl Default for ReconnectConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 10,
+            retry_backoff: Duration::from_secs(1),
+        }
+    }
+}
+
+async fn redial_with_retry(
+    redial: &RedialStrategy,
+    reconnect: &ReconnectConfig,
+) -> eyre::Result<TcpStream> {
+    let mut attempt = 0;
+    loop {
+        match redial.redial().await {
+            Ok(stream) => return Ok(stream),
+            Err(err) if attempt < reconnect.max_retries => {
+                attempt += 1;
+                tracing::warn!(
+                    "reconnect attempt {attempt}/{} failed: {err:?}, retrying in {:?}",
+                    reconnect.max_retries,
+                    reconnect.retry_backoff
+                );
+                tokio::time::sleep(reconnect.retry_backoff).await;
+            }
+            Err(err) => return Err(err).context("exhausted reconnect retry budget"),
+        }
+    }
+}
+
+/// Exchanges each side's `last_received_seq` directly over the freshly re-dialed `stream` (ahead
+/// of re-wrapping it in a `LengthDelimitedCodec`), so [`ResendBuffer::ack`] can drop whatever the
+/// peer already has before replaying the rest.
+async fn resync(
+    stream: &mut TcpStream,
+    last_received_seq: u64,
+    resend: &ResendBuffer,
+) -> eyre::Result<()> {
+    stream.write_all(&last_received_seq.to_be_bytes()).await?;
+    let mut peer_last_received_seq = [0; 8];
+    stream.read_exact(&mut peer_last_received_seq).await?;
+    resend.ack(u64::from_be_bytes(peer_last_received_seq));
+    Ok(())
+}
+
+/// Runs one direction of a [`TcpNetwork`] link: pulls chunks off `queue` and writes them to the
+/// wire, decodes incoming frames and feeds reassembled messages to `recv_tx`, and - the crux of
+/// chunk2-5 - transparently redials and resyncs via `redial`/[`resync`] on a transport error
+/// instead of giving up, replaying whatever `resend` shows the peer hasn't acked yet.
+///
+/// Mirrors the reconnect/replay approach in mt_rudp: every outgoing frame gets a sequence number,
+/// frames are acked by the peer, and unacked frames are replayed verbatim after reconnecting.
+async fn run_link(
+    mut stream: TcpStream,
+    queue: Arc<SendQueue>,
+    recv_tx: mpsc::Sender<eyre::Result<Vec<u8>>>,
+    cancellation_token: CancellationToken,
+    redial: RedialStrategy,
+    reconnect: ReconnectConfig,
+) {
+    let resend = ResendBuffer::default();
+    let mut last_received_seq = 0u64;
+    let mut reassembly = ChunkReassembly::default();
+
+    loop {
+        let mut framed = Framed::new(stream, LengthDelimitedCodec::new());
+        for frame in resend.snapshot() {
+            if let Err(err) = framed.send(frame.into()).await {
+                tracing::warn!("failed to replay frame after reconnect: {err:?}");
+            }
+        }
+
+        let mut write_done = false;
+        let err = 'io: loop {
+            let next_frame = async {
+                if cancellation_token.is_cancelled() {
+                    // Borrowing netapp's "do not close connections immediately on close signal,
+                    // await for remaining responses" behavior: keep waiting, but only for up to
+                    // `DRAIN_TIMEOUT`, so bytes already in flight from the peer still get
+                    // delivered instead of being discarded the instant shutdown starts.
+                    match tokio::time::timeout(DRAIN_TIMEOUT, framed.next()).await {
+                        Ok(frame) => frame,
+                        Err(_) => {
+                            tracing::warn!(
+                                "drain timeout elapsed waiting for peer to close connection"
+                            );
+                            None
+                        }
+                    }
+                } else {
+                    framed.next().await
+                }
+            };
+
+            tokio::select! {
+                biased;
+                frame = next_frame => match frame {
+                    Some(Ok(frame)) => match decode_frame(&frame) {
+                        Ok(DecodedFrame::Ack { ack_seq }) => resend.ack(ack_seq),
+                        Ok(DecodedFrame::Data { seq, request_id, more_follows, payload }) => {
+                            last_received_seq = last_received_seq.max(seq);
+                            let ack = encode_ack_frame(last_received_seq);
+                            if let Err(err) = framed.send(ack.into()).await {
+                                break 'io err.into();
+                            }
+                            let Some(message) = reassembly.push(request_id, more_follows, payload) else {
+                                continue;
+                            };
+                            if recv_tx.send(Ok(message)).await.is_err() {
+                                tracing::warn!("recv receiver dropped");
+                                return;
+                            }
+                        }
+                        Err(err) => break 'io err,
+                    },
+                    Some(Err(err)) => break 'io err.into(),
+                    // The peer closed its write half: draining is complete if we're the one
+                    // shutting down, otherwise the peer dropped the connection unexpectedly.
+                    None if write_done || cancellation_token.is_cancelled() => return,
+                    None => break 'io eyre::eyre!("peer closed the connection unexpectedly"),
+                },
+                chunk = queue.next_chunk(), if !write_done => match chunk {
+                    Some((request_id, more_follows, payload)) => {
+                        let seq = resend.next_seq();
+                        let frame = encode_data_frame(seq, request_id, more_follows, &payload);
+                        resend.record(seq, frame.clone());
+                        if let Err(err) = framed.send(frame.into()).await {
+                            break 'io err.into();
+                        }
+                    }
+                    None => {
+                        // The queue only reports drained-and-closed once `TcpNetwork::shutdown`
+                        // closed it and every already-queued chunk went out - so it's safe to
+                        // flush and half-close the write side for the peer to notice.
+                        if let Err(err) = framed.close().await {
+                            tracing::warn!("failed to flush/shutdown write half: {err:?}");
+                        }
+                        write_done = true;
+                    }
+                },
+            }
+        };
+
+        if write_done && cancellation_token.is_cancelled() {
+            let _ = recv_tx.send(Err(err)).await;
+            return;
+        }
+        tracing::warn!("tcp link interrupted, attempting to reconnect: {err:?}");
+
+        stream = match redial_with_retry(&redial, &reconnect).await {
+            Ok(stream) => stream,
+            Err(err) => {
+                let _ = recv_tx.send(Err(err)).await;
+                return;
+            }
+        };
+        if let Err(err) = resync(&mut stream, last_received_seq, &resend).await {
+            let _ = recv_tx.send(Err(err)).await;
+            return;
+        }
+    }
+}
+
 #[derive(Debug)]
 #[expect(clippy::complexity)]
 pub struct TcpNetwork {
     id: PartyID,
     // TODO could replace map with something simpler, we only need 3 parties
-    send: HashMap<usize, (mpsc::Sender<Vec<u8>>, AtomicUsize)>,
+    send: HashMap<usize, (Arc<SendQueue>, AtomicUsize)>,
     recv: HashMap<usize, (Mutex<mpsc::Receiver<eyre::Result<Vec<u8>>>>, AtomicUsize)>,
+    link_tasks: Vec<JoinHandle<()>>,
 }
 
 impl TcpNetwork {
+    #[expect(clippy::too_many_arguments)]
     pub fn new(
         id: PartyID,
         next_stream: TcpStream,
+        next_redial: RedialStrategy,
         prev_stream: TcpStream,
+        prev_redial: RedialStrategy,
+        reconnect: ReconnectConfig,
         cancellation_token: CancellationToken,
     ) -> eyre::Result<Self> {
         let mut send = HashMap::new();
         let mut recv = HashMap::new();
-
-        let codec = LengthDelimitedCodec::new();
-        let next_stream = Framed::new(next_stream, codec.clone());
-        let prev_stream = Framed::new(prev_stream, codec);
-
-        let (mut next_sender, mut next_receiver) = next_stream.split();
-        let (mut prev_sender, mut prev_receiver) = prev_stream.split();
+        let mut link_tasks = Vec::new();
 
         // TODO deduplicate for prev and next
-        let (next_send_tx, mut next_send_rx) = mpsc::channel::<Vec<u8>>(32);
+        let next_queue = Arc::new(SendQueue::new());
         let (next_recv_tx, next_recv_rx) = mpsc::channel::<eyre::Result<Vec<u8>>>(32);
-        tokio::task::spawn(async move {
-            while let Some(data) = next_send_rx.recv().await {
-                if let Err(err) = next_sender.send(data.into()).await {
-                    tracing::warn!("failed to send data: {err:?}");
-                    break;
-                }
-            }
-        });
-        let cancellation_token_clone = cancellation_token.clone();
-        tokio::task::spawn(async move {
-            loop {
-                tokio::select! {
-                    _ = cancellation_token_clone.cancelled() => {
-                        break;
-                    }
-                    msg = next_receiver.next() => {
-                        match msg {
-                            Some(Ok(data)) => {
-                                if next_recv_tx.send(Ok(data.into())).await.is_err() {
-                                    tracing::warn!("recv receiver dropped");
-                                    break;
-                                }
-                            }
-                            Some(Err(err)) => {
-                                let _ = next_recv_tx.send(Err(eyre::eyre!("tcp error: {err}"))).await;
-                                break;
-                            }
-                            None => break,
-                        }
-                    }
-                }
-            }
-        });
+        link_tasks.push(tokio::task::spawn(run_link(
+            next_stream,
+            Arc::clone(&next_queue),
+            next_recv_tx,
+            cancellation_token.clone(),
+            next_redial,
+            reconnect,
+        )));
 
-        let (prev_send_tx, mut prev_send_rx) = mpsc::channel::<Vec<u8>>(32);
+        let prev_queue = Arc::new(SendQueue::new());
         let (prev_recv_tx, prev_recv_rx) = mpsc::channel::<eyre::Result<Vec<u8>>>(32);
-        tokio::task::spawn(async move {
-            while let Some(data) = prev_send_rx.recv().await {
-                if let Err(err) = prev_sender.send(data.into()).await {
-                    tracing::warn!("failed to send data: {err:?}");
-                    break;
-                }
-            }
-        });
-        let cancellation_token_clone = cancellation_token.clone();
-        tokio::task::spawn(async move {
-            loop {
-                tokio::select! {
-                    _ = cancellation_token_clone.cancelled() => {
-                        break;
-                    }
-                    msg = prev_receiver.next() => {
-                        match msg {
-                            Some(Ok(data)) => {
-                                if prev_recv_tx.send(Ok(data.into())).await.is_err() {
-                                    tracing::warn!("recv receiver dropped");
-                                    break;
-                                }
-                            }
-                            Some(Err(err)) => {
-                                let _ = prev_recv_tx.send(Err(eyre::eyre!("tcp error: {err}"))).await;
-                                break;
-                            }
-                            None => break,
-                        }
-                    }
-                }
-            }
-        });
+        link_tasks.push(tokio::task::spawn(run_link(
+            prev_stream,
+            Arc::clone(&prev_queue),
+            prev_recv_tx,
+            cancellation_token,
+            prev_redial,
+            reconnect,
+        )));
 
-        send.insert(id.next().into(), (next_send_tx, AtomicUsize::default()));
-        send.insert(id.prev().into(), (prev_send_tx, AtomicUsize::default()));
+        send.insert(id.next().into(), (next_queue, AtomicUsize::default()));
+        send.insert(id.prev().into(), (prev_queue, AtomicUsize::default()));
         recv.insert(
             id.next().into(),
             (Mutex::new(next_recv_rx), AtomicUsize::default()),
@@ -203,7 +686,43 @@ impl TcpNetwork {
             (Mutex::new(prev_recv_rx), AtomicUsize::default()),
         );
 
-        Ok(Self { id, send, recv })
+        Ok(Self {
+            id,
+            send,
+            recv,
+            link_tasks,
+        })
+    }
+
+    /// Like [`Network::send`], but lets the caller pick how this message should be scheduled
+    /// relative to other messages queued for `to` - see [`Priority`].
+    pub fn send_with_priority(
+        &self,
+        to: usize,
+        data: &[u8],
+        priority: Priority,
+    ) -> eyre::Result<()> {
+        let (queue, sent_bytes) = self.send.get(&to).context("party id out-of-bounds")?;
+        sent_bytes.fetch_add(data.len(), Ordering::Relaxed);
+        queue.push(priority, data.to_vec());
+        Ok(())
+    }
+
+    /// Gracefully ends the connection instead of the abrupt teardown that dropping a
+    /// [`TcpNetwork`] mid-round would cause: consuming `self` stops any further
+    /// [`Network::send`]/[`Network::recv`] calls from being possible, which lets each link task
+    /// drain whatever was already queued, flush it, and shut down its write half, while it keeps
+    /// receiving until the peer does the same (or, once the cancellation token has fired,
+    /// [`DRAIN_TIMEOUT`] elapses). Resolves only once
+    /// both directions have finished draining.
+    pub async fn shutdown(self) -> eyre::Result<()> {
+        for (queue, _) in self.send.values() {
+            queue.close();
+        }
+        for task in self.link_tasks {
+            task.await.context("link task panicked during shutdown")?;
+        }
+        Ok(())
     }
 }
 
@@ -213,10 +732,7 @@ impl Network for TcpNetwork {
     }
 
     fn send(&self, to: usize, data: &[u8]) -> eyre::Result<()> {
-        let (sender, sent_bytes) = self.send.get(&to).context("party id out-of-bounds")?;
-        sent_bytes.fetch_add(data.len(), std::sync::atomic::Ordering::Relaxed);
-        sender.blocking_send(data.to_vec())?;
-        Ok(())
+        self.send_with_priority(to, data, Priority::default())
     }
 
     fn recv(&self, from: usize) -> eyre::Result<Vec<u8>> {
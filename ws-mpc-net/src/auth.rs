@@ -0,0 +1,161 @@
+//! Handshake-authenticated, encrypted transport.
+//!
+//! [`WebSocketNetwork`] trusts whatever is on the other end of the socket (TLS termination, if
+//! any, aside). [`AuthenticatedWebSocketNetwork`] wraps it so that, right after the usual
+//! websocket connect, each side proves possession of its static X25519 key - the same keypair
+//! loaded from `secret_key_path`/`dev-keys` - before any MPC traffic flows: both parties exchange
+//! their public key over the freshly-established link, verify it against the [`PublicKey`]
+//! configured for that party id, and derive a shared box from it. Every frame handed to
+//! [`Network::send`]/[`Network::recv`] is then sealed/opened with that box (XSalsa20-Poly1305),
+//! so the wire only ever carries authenticated ciphertext.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use crypto_box::aead::{Aead, generic_array::GenericArray};
+use crypto_box::{PublicKey, SalsaBox, SecretKey};
+use eyre::{Context as _, ContextCompat as _};
+use mpc_core::protocols::rep3::id::PartyID;
+use mpc_net::{ConnectionStats, Network};
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use crate::{ClientWsStream, ReconnectConfig, ServerWsStream, WebSocketNetwork, WsSessions};
+
+/// Marker for a type that establishes an MPC [`Network`] link and then behaves like one, so
+/// callers can select a concrete transport (plain or authenticated) without changing how they
+/// use it afterwards.
+pub trait PeerTransport: Network {}
+
+impl PeerTransport for WebSocketNetwork {}
+impl PeerTransport for AuthenticatedWebSocketNetwork {}
+
+/// This party's long-term identity, used to authenticate both ends of every
+/// [`AuthenticatedWebSocketNetwork`] link.
+#[derive(Clone)]
+pub struct PeerIdentity {
+    /// This party's static secret key, loaded the same way as for sealing MPC shares to clients.
+    pub secret_key: SecretKey,
+    /// Expected static public key of every other party, keyed by [`PartyID`] index.
+    pub peer_public_keys: HashMap<usize, PublicKey>,
+}
+
+/// Derives the nonce a frame from `sender` to `other` used for sequence number `seq`.
+///
+/// A link's two directions share one symmetric box (the X25519 shared secret doesn't depend on
+/// who's sending), so the sequence number alone isn't enough to keep nonces unique - both sides
+/// would start counting from zero. Tagging the nonce with which side is numerically the greater
+/// party id keeps the two directions' nonce spaces disjoint without any extra coordination.
+fn nonce(sender: usize, other: usize, seq: u64) -> crypto_box::Nonce {
+    let mut bytes = [0u8; 24];
+    bytes[0] = u8::from(sender > other);
+    bytes[1..9].copy_from_slice(&seq.to_be_bytes());
+    GenericArray::clone_from_slice(&bytes)
+}
+
+/// A [`Network`] that authenticates and encrypts every frame sent over an inner
+/// [`WebSocketNetwork`]. See the [module docs](self) for the handshake this runs on construction.
+pub struct AuthenticatedWebSocketNetwork {
+    inner: WebSocketNetwork,
+    local_id: usize,
+    boxes: HashMap<usize, SalsaBox>,
+    send_seq: HashMap<usize, AtomicU64>,
+}
+
+impl AuthenticatedWebSocketNetwork {
+    #[expect(clippy::too_many_arguments)]
+    pub fn new(
+        id: PartyID,
+        session_id: Uuid,
+        next_peer_url: String,
+        next_websocket: ClientWsStream,
+        ws_sessions: WsSessions,
+        prev_websocket: ServerWsStream,
+        cancellation_token: CancellationToken,
+        reconnect_config: ReconnectConfig,
+        idle_timeout: Duration,
+        ping_interval: Duration,
+        identity: PeerIdentity,
+    ) -> eyre::Result<Self> {
+        let inner = WebSocketNetwork::new(
+            id,
+            session_id,
+            next_peer_url,
+            next_websocket,
+            ws_sessions,
+            prev_websocket,
+            cancellation_token,
+            reconnect_config,
+            idle_timeout,
+            ping_interval,
+        )?;
+
+        let local_id = id.into();
+        let mut boxes = HashMap::new();
+        for party in [usize::from(id.next()), usize::from(id.prev())] {
+            let expected_pk = identity
+                .peer_public_keys
+                .get(&party)
+                .with_context(|| format!("no configured public key for party {party}"))?;
+
+            // Handshake: exchange static public keys in cleartext over the just-established
+            // link, then refuse to proceed unless the remote is really who we expect.
+            inner.send(party, identity.secret_key.public_key().as_bytes())?;
+            let their_key_bytes = inner.recv(party)?;
+            let their_key = PublicKey::from_slice(&their_key_bytes)
+                .context("peer sent an invalid handshake public key")?;
+            eyre::ensure!(
+                their_key.as_bytes() == expected_pk.as_bytes(),
+                "party {party} presented an unexpected public key during handshake"
+            );
+
+            boxes.insert(party, SalsaBox::new(&their_key, &identity.secret_key));
+        }
+
+        let send_seq = boxes.keys().map(|party| (*party, AtomicU64::new(0))).collect();
+        Ok(Self {
+            inner,
+            local_id,
+            boxes,
+            send_seq,
+        })
+    }
+}
+
+impl Network for AuthenticatedWebSocketNetwork {
+    fn id(&self) -> usize {
+        self.inner.id()
+    }
+
+    fn send(&self, to: usize, data: &[u8]) -> eyre::Result<()> {
+        let sealed_box = self.boxes.get(&to).context("party id out-of-bounds")?;
+        let seq = self
+            .send_seq
+            .get(&to)
+            .context("party id out-of-bounds")?
+            .fetch_add(1, Ordering::SeqCst);
+        let ciphertext = sealed_box
+            .encrypt(&nonce(self.local_id, to, seq), data)
+            .map_err(|err| eyre::eyre!("failed to encrypt frame: {err}"))?;
+        let mut frame = Vec::with_capacity(8 + ciphertext.len());
+        frame.extend_from_slice(&seq.to_be_bytes());
+        frame.extend_from_slice(&ciphertext);
+        self.inner.send(to, &frame)
+    }
+
+    fn recv(&self, from: usize) -> eyre::Result<Vec<u8>> {
+        let sealed_box = self.boxes.get(&from).context("party id out-of-bounds")?;
+        let frame = self.inner.recv(from)?;
+        eyre::ensure!(frame.len() >= 8, "authenticated frame too short");
+        let (seq_bytes, ciphertext) = frame.split_at(8);
+        let seq = u64::from_be_bytes(seq_bytes.try_into().expect("checked length above"));
+        sealed_box
+            .decrypt(&nonce(from, self.local_id, seq), ciphertext)
+            .map_err(|err| eyre::eyre!("failed to authenticate frame from party {from}: {err}"))
+    }
+
+    fn get_connection_stats(&self) -> ConnectionStats {
+        self.inner.get_connection_stats()
+    }
+}
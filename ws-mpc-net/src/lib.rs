@@ -1,4 +1,7 @@
+use std::collections::VecDeque;
 use std::str::FromStr as _;
+use std::sync::atomic::{AtomicU8, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
 use std::sync::{Arc, Mutex};
 use std::{collections::HashMap, sync::atomic::AtomicUsize};
 
@@ -17,38 +20,122 @@ use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
 use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
+pub mod auth;
+pub mod mux;
+
 const SESSION_ID_HEADER: &str = "session_id";
 
+/// Upper bound on how many unacknowledged frames are kept around per link so they can be
+/// replayed after a reconnect, in case the peer's piggybacked `ack` never catches up (e.g. the
+/// link drops before a single ack makes it back). Frames are normally trimmed as soon as the
+/// peer's `ack` confirms receipt; this cap is just a backstop against unbounded growth.
+const RESEND_BUFFER_CAP: usize = 256;
+
 pub type ServerWsStream = WebSocket;
 
 pub type ClientWsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
 
 #[expect(clippy::large_enum_variant)]
 pub enum WsSession {
-    Ready(ServerWsStream),
-    Waiter(oneshot::Sender<ServerWsStream>),
+    Ready(ServerWsStream, Instant),
+    Waiter(oneshot::Sender<ServerWsStream>, Instant),
+}
+
+/// Tunables for the reaper task [`WsSessions::new`] spawns, and the capacity bound enforced by
+/// [`WsSessions::handle_ws_request`] - see [`WsSessions`].
+#[derive(Debug, Clone, Copy)]
+pub struct WsSessionsConfig {
+    /// How long a `Ready` websocket may sit uncollected before the reaper closes and drops it.
+    pub session_ttl: Duration,
+    /// How long a `Waiter` may wait for its peer to connect before the reaper fails it.
+    pub waiter_timeout: Duration,
+    /// How often the reaper sweeps the session table for expired entries.
+    pub reap_interval: Duration,
+    /// How many pending (not-yet-matched) sessions `handle_ws_request` admits before it starts
+    /// rejecting new upgrades with `503`.
+    pub max_pending_sessions: usize,
+}
+
+impl Default for WsSessionsConfig {
+    fn default() -> Self {
+        Self {
+            session_ttl: Duration::from_secs(60),
+            waiter_timeout: Duration::from_secs(60),
+            reap_interval: Duration::from_secs(10),
+            max_pending_sessions: 1024,
+        }
+    }
 }
 
-#[derive(Clone, Default)]
+#[derive(Clone)]
 pub struct WsSessions {
-    // TODO if websockets are not collected from this map, they pile up and never get removed
     sessions: Arc<tokio::sync::Mutex<HashMap<Uuid, WsSession>>>,
+    config: WsSessionsConfig,
 }
 
 impl WsSessions {
+    pub fn new(config: WsSessionsConfig) -> Self {
+        let sessions = Self {
+            sessions: Arc::default(),
+            config,
+        };
+
+        let sessions_clone = sessions.clone();
+        tokio::spawn(async move {
+            sessions_clone.reap().await;
+        });
+
+        sessions
+    }
+
+    /// Periodically evicts `Ready` websockets older than `config.session_ttl` and fails
+    /// `Waiter`s that have been pending past `config.waiter_timeout`, so a peer that never
+    /// shows up (or never gets collected) doesn't leak a slot in `sessions` forever.
+    async fn reap(&self) {
+        let mut interval = tokio::time::interval(self.config.reap_interval);
+        loop {
+            interval.tick().await;
+            let mut sessions = self.sessions.lock().await;
+            sessions.retain(|session_id, session| match session {
+                WsSession::Ready(_, inserted_at) => {
+                    let expired = inserted_at.elapsed() > self.config.session_ttl;
+                    if expired {
+                        tracing::warn!(
+                            "reaping uncollected ws session {session_id} after {:?}",
+                            inserted_at.elapsed()
+                        );
+                    }
+                    !expired
+                }
+                WsSession::Waiter(_, registered_at) => {
+                    let expired = registered_at.elapsed() > self.config.waiter_timeout;
+                    if expired {
+                        tracing::warn!(
+                            "timing out waiter for ws session {session_id} after {:?}",
+                            registered_at.elapsed()
+                        );
+                        // Dropping the sender wakes the waiting `get` with a recv error instead
+                        // of leaving it pending forever.
+                    }
+                    !expired
+                }
+            });
+        }
+    }
+
     pub async fn get(&self, session_id: Uuid) -> eyre::Result<WebSocket> {
         let mut sessions = self.sessions.lock().await;
         let session = sessions.remove(&session_id);
         match session {
-            Some(WsSession::Ready(websocket)) => Ok(websocket),
-            Some(WsSession::Waiter(_)) => {
+            Some(WsSession::Ready(websocket, _)) => Ok(websocket),
+            Some(WsSession::Waiter(..)) => {
                 eyre::bail!("tried to get same session twice")
             }
             None => {
                 let (tx, rx) = oneshot::channel();
-                sessions.insert(session_id, WsSession::Waiter(tx));
+                sessions.insert(session_id, WsSession::Waiter(tx, Instant::now()));
                 drop(sessions); // drop to release lock
-                Ok(rx.await?)
+                rx.await.context("timed out waiting for peer to connect")
             }
         }
     }
@@ -57,14 +144,14 @@ impl WsSessions {
         let mut sessions = self.sessions.lock().await;
         let session = sessions.remove(&session_id);
         match session {
-            Some(WsSession::Ready(_)) => {
+            Some(WsSession::Ready(..)) => {
                 eyre::bail!("tried to insert same session twice")
             }
-            Some(WsSession::Waiter(tx)) => {
+            Some(WsSession::Waiter(tx, _)) => {
                 let _ = tx.send(websocket);
             }
             None => {
-                sessions.insert(session_id, WsSession::Ready(websocket));
+                sessions.insert(session_id, WsSession::Ready(websocket, Instant::now()));
             }
         }
         Ok(())
@@ -98,6 +185,15 @@ impl WsSessions {
                 format!("invalid header value for \"{SESSION_ID_HEADER}\""),
             )
         })?;
+
+        if self.sessions.lock().await.len() >= self.config.max_pending_sessions {
+            return Err((
+                StatusCode::SERVICE_UNAVAILABLE,
+                "too many pending websocket sessions".to_string(),
+            )
+                .into());
+        }
+
         tracing::debug!("ws upgrade for session {session_id}");
         let sessions = self.clone();
         let response = ws.on_upgrade(move |socket| async move {
@@ -128,6 +224,153 @@ pub async fn ws_connect(ws_url: &str, session_id: Uuid) -> eyre::Result<ClientWs
     Ok(websocket)
 }
 
+/// Backoff schedule used while a [`WebSocketNetwork`] link tries to re-establish itself after a
+/// disconnect.
+#[derive(Debug, Clone)]
+pub struct ReconnectConfig {
+    /// Number of redial attempts before a link is given up on and marked [`ConnectionState::Dead`].
+    pub max_attempts: u32,
+    /// Delay before the first redial attempt.
+    pub initial_backoff: Duration,
+    /// Upper bound the exponential backoff is capped at.
+    pub max_backoff: Duration,
+}
+
+impl Default for ReconnectConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 10,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Observable state of one direction (next/prev) of a [`WebSocketNetwork`], exposed via
+/// [`WebSocketNetwork::connection_state`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    /// The underlying websocket is up and frames are flowing.
+    Connected,
+    /// The link dropped and a redial with backoff is in progress.
+    Reconnecting,
+    /// Reconnection attempts were exhausted; this link will not recover on its own.
+    Dead,
+}
+
+impl ConnectionState {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0 => Self::Connected,
+            1 => Self::Reconnecting,
+            _ => Self::Dead,
+        }
+    }
+}
+
+/// Returned (wrapped in an [`eyre::Report`]) when a link receives nothing — not even a Pong — for
+/// longer than its configured idle timeout.
+#[derive(Debug, thiserror::Error)]
+#[error("no data received from peer within {0:?}")]
+pub struct PeerTimeout(pub Duration);
+
+/// Retries `connect` with exponential backoff according to `config`, giving up once
+/// `config.max_attempts` is exhausted.
+async fn reconnect_with_backoff<F, Fut, T>(config: &ReconnectConfig, mut connect: F) -> eyre::Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = eyre::Result<T>>,
+{
+    let mut backoff = config.initial_backoff;
+    for attempt in 0..config.max_attempts {
+        match connect().await {
+            Ok(value) => return Ok(value),
+            Err(err) => {
+                tracing::warn!(
+                    "reconnect attempt {}/{} failed: {err:?}, retrying in {backoff:?}",
+                    attempt + 1,
+                    config.max_attempts
+                );
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(config.max_backoff);
+            }
+        }
+    }
+    eyre::bail!("exhausted {} reconnect attempts", config.max_attempts)
+}
+
+/// Prefixes `payload` with its monotonically increasing sequence number (so the receiving side
+/// can detect replays/duplicates after a reconnect) and the sender's `ack` - the sequence number
+/// it expects next, one past the highest it has received so far - piggybacked so the peer can trim its
+/// resend buffer down to only the frames that are still unacknowledged.
+fn encode_frame(seq: u64, ack: u64, payload: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(16 + payload.len());
+    frame.extend_from_slice(&seq.to_be_bytes());
+    frame.extend_from_slice(&ack.to_be_bytes());
+    frame.extend_from_slice(payload);
+    frame
+}
+
+/// Splits a frame produced by [`encode_frame`] back into its sequence number, piggybacked ack,
+/// and payload.
+fn decode_frame(data: Vec<u8>) -> eyre::Result<(u64, u64, Vec<u8>)> {
+    eyre::ensure!(
+        data.len() >= 16,
+        "frame too short to contain a sequence number and ack"
+    );
+    let (seq, rest) = data.split_at(8);
+    let (ack, payload) = rest.split_at(8);
+    let seq = u64::from_be_bytes(seq.try_into().expect("checked length above"));
+    let ack = u64::from_be_bytes(ack.try_into().expect("checked length above"));
+    Ok((seq, ack, payload.to_vec()))
+}
+
+/// Drives one link of [`WebSocketNetwork::from_mux_handles`] (`next` or `prev`, named by
+/// `label` for logging): passes outbound payloads straight to `sender` and inbound ones straight
+/// to `recv_tx`, with no resend buffer or redial - unlike the raw-socket links [`WebSocketNetwork::new`]
+/// spawns, there's nothing this link alone could redial, since the whole shared `MuxConnection`
+/// would need to be re-established, which is a connection-level concern this function's caller
+/// owns, not this one. A closed handle or an idle timeout just marks the link `Dead`.
+async fn run_mux_link(
+    label: &'static str,
+    sender: mux::MuxSessionSender,
+    mut in_rx: mpsc::Receiver<Vec<u8>>,
+    cancellation_token: CancellationToken,
+    idle_timeout: Duration,
+    state: Arc<AtomicU8>,
+    mut send_rx: mpsc::Receiver<Vec<u8>>,
+    recv_tx: mpsc::Sender<eyre::Result<Vec<u8>>>,
+) {
+    state.store(ConnectionState::Connected as u8, Ordering::SeqCst);
+    let dead_reason = loop {
+        tokio::select! {
+            _ = cancellation_token.cancelled() => return,
+            maybe_data = send_rx.recv() => {
+                let Some(data) = maybe_data else { return };
+                if let Err(err) = sender.send(data).await {
+                    break eyre::eyre!("failed to send data to {label} peer: {err:#}");
+                }
+            }
+            msg = tokio::time::timeout(idle_timeout, in_rx.recv()) => {
+                match msg {
+                    Ok(Some(payload)) => {
+                        if recv_tx.send(Ok(payload)).await.is_err() {
+                            tracing::warn!("recv receiver dropped");
+                            return;
+                        }
+                        continue;
+                    }
+                    Ok(None) => break eyre::eyre!("{label} peer's mux session closed"),
+                    Err(_) => break eyre::Report::new(PeerTimeout(idle_timeout)),
+                }
+            }
+        }
+    };
+    tracing::warn!("{label} peer link dead (no redial for multiplexed sessions): {dead_reason:?}");
+    state.store(ConnectionState::Dead as u8, Ordering::SeqCst);
+    let _ = recv_tx.send(Err(dead_reason)).await;
+}
+
 #[derive(Debug)]
 #[expect(clippy::complexity)]
 pub struct WebSocketNetwork {
@@ -135,109 +378,339 @@ pub struct WebSocketNetwork {
     // TODO could replace map with something simpler, we only need 3 parties
     send: HashMap<usize, (mpsc::Sender<Vec<u8>>, AtomicUsize)>,
     recv: HashMap<usize, (Mutex<mpsc::Receiver<eyre::Result<Vec<u8>>>>, AtomicUsize)>,
+    state: HashMap<usize, Arc<AtomicU8>>,
 }
 
 impl WebSocketNetwork {
+    /// Builds an MPC [`Network`] over two already-established websocket links (one dialed to
+    /// `next_peer_url`, one parked in `ws_sessions` by the previous party) and supervises both:
+    /// if either direction drops, it is transparently redialed with backoff (governed by
+    /// `reconnect_config`) while outbound frames keep flowing into the existing bounded channel,
+    /// and a sequence number on every frame lets the receiving side drop duplicates replayed
+    /// after the reconnect.
+    ///
+    /// Each link also sends a WebSocket Ping every `ping_interval` and is considered dead -
+    /// surfacing a [`PeerTimeout`] and triggering the same reconnect path as any other link
+    /// failure - if nothing at all (not even a Pong) arrives within `idle_timeout`.
+    #[expect(clippy::too_many_arguments)]
     pub fn new(
         id: PartyID,
+        session_id: Uuid,
+        next_peer_url: String,
         next_websocket: ClientWsStream,
+        ws_sessions: WsSessions,
         prev_websocket: ServerWsStream,
         cancellation_token: CancellationToken,
+        reconnect_config: ReconnectConfig,
+        idle_timeout: Duration,
+        ping_interval: Duration,
     ) -> eyre::Result<Self> {
         let mut send = HashMap::new();
         let mut recv = HashMap::new();
-
-        let (mut next_sender, mut next_receiver) = next_websocket.split();
-        let (mut prev_sender, mut prev_receiver) = prev_websocket.split();
+        let mut state = HashMap::new();
 
         // TODO deduplicate for prev and next
         let (next_send_tx, mut next_send_rx) = mpsc::channel::<Vec<u8>>(32);
         let (next_recv_tx, next_recv_rx) = mpsc::channel::<eyre::Result<Vec<u8>>>(32);
-        tokio::task::spawn(async move {
-            while let Some(data) = next_send_rx.recv().await {
-                if let Err(err) = next_sender
-                    .send(tokio_tungstenite::tungstenite::Message::Binary(data.into()))
-                    .await
-                {
-                    tracing::warn!("failed to send data: {err:?}");
-                    break;
-                }
-            }
-        });
-        let cancellation_token_clone = cancellation_token.clone();
-        tokio::task::spawn(async move {
-            loop {
-                tokio::select! {
-                    _ = cancellation_token_clone.cancelled() => {
-                        break;
-                    }
-                    msg = next_receiver.next() => {
-                        match msg {
-                            Some(Ok(tokio_tungstenite::tungstenite::Message::Binary(data))) => {
-                                if next_recv_tx.send(Ok(data.into())).await.is_err() {
-                                    tracing::warn!("recv receiver dropped");
+        let next_state = Arc::new(AtomicU8::new(ConnectionState::Connected as u8));
+        {
+            let cancellation_token = cancellation_token.clone();
+            let state = next_state.clone();
+            let reconnect_config = reconnect_config.clone();
+            tokio::task::spawn(async move {
+                let (mut sender, mut receiver) = next_websocket.split();
+                let seq_counter = AtomicU64::new(0);
+                let next_expected = AtomicU64::new(0);
+                let resend_buffer: Mutex<VecDeque<(u64, Vec<u8>)>> = Mutex::new(VecDeque::new());
+                'outer: loop {
+                    state.store(ConnectionState::Connected as u8, Ordering::SeqCst);
+                    let mut ping_ticker = tokio::time::interval(ping_interval);
+                    ping_ticker.reset();
+                    let mut timed_out = false;
+                    loop {
+                        tokio::select! {
+                            _ = cancellation_token.cancelled() => {
+                                return;
+                            }
+                            _ = ping_ticker.tick() => {
+                                if sender
+                                    .send(tokio_tungstenite::tungstenite::Message::Ping(Vec::new().into()))
+                                    .await
+                                    .is_err()
+                                {
+                                    tracing::warn!("failed to send ping to next peer, reconnecting");
                                     break;
                                 }
                             }
-                            Some(Ok(_)) => {
-                                tracing::warn!("unexpected ws message: {msg:?}");
-                                let _ = next_recv_tx.send(Err(eyre::eyre!("invalid ws message"))).await;
-                                break;
+                            maybe_data = next_send_rx.recv() => {
+                                let Some(data) = maybe_data else { return };
+                                let seq = seq_counter.fetch_add(1, Ordering::SeqCst);
+                                let ack = next_expected.load(Ordering::SeqCst);
+                                let frame = encode_frame(seq, ack, &data);
+                                {
+                                    let mut buf = resend_buffer.lock().expect("not poisoned");
+                                    buf.push_back((seq, frame.clone()));
+                                    while buf.len() > RESEND_BUFFER_CAP {
+                                        buf.pop_front();
+                                    }
+                                }
+                                if let Err(err) = sender
+                                    .send(tokio_tungstenite::tungstenite::Message::Binary(frame.into()))
+                                    .await
+                                {
+                                    tracing::warn!("failed to send data to next peer, reconnecting: {err:?}");
+                                    break;
+                                }
+                            }
+                            msg = tokio::time::timeout(idle_timeout, receiver.next()) => {
+                                let Ok(msg) = msg else {
+                                    tracing::warn!("idle timeout waiting for next peer, reconnecting");
+                                    timed_out = true;
+                                    break;
+                                };
+                                match msg {
+                                    Some(Ok(tokio_tungstenite::tungstenite::Message::Binary(data))) => {
+                                        match decode_frame(data.into()) {
+                                            Ok((seq, ack, payload)) => {
+                                                // The peer has confirmed receiving everything
+                                                // before `ack` - those frames no longer need to
+                                                // survive a reconnect.
+                                                let mut buf = resend_buffer.lock().expect("not poisoned");
+                                                while buf.front().is_some_and(|(s, _)| *s < ack) {
+                                                    buf.pop_front();
+                                                }
+                                                drop(buf);
+
+                                                if seq >= next_expected.load(Ordering::SeqCst) {
+                                                    next_expected.store(seq + 1, Ordering::SeqCst);
+                                                    if next_recv_tx.send(Ok(payload)).await.is_err() {
+                                                        tracing::warn!("recv receiver dropped");
+                                                        return;
+                                                    }
+                                                }
+                                            }
+                                            Err(err) => {
+                                                let _ = next_recv_tx.send(Err(err)).await;
+                                            }
+                                        }
+                                    }
+                                    Some(Ok(tokio_tungstenite::tungstenite::Message::Ping(payload))) => {
+                                        let _ = sender
+                                            .send(tokio_tungstenite::tungstenite::Message::Pong(payload))
+                                            .await;
+                                    }
+                                    Some(Ok(tokio_tungstenite::tungstenite::Message::Pong(_))) => {}
+                                    Some(Ok(tokio_tungstenite::tungstenite::Message::Close(frame))) => {
+                                        tracing::info!(?frame, "next peer closed websocket cleanly, reconnecting");
+                                        break;
+                                    }
+                                    Some(Ok(_)) => {
+                                        tracing::warn!("unexpected ws message: {msg:?}");
+                                        break;
+                                    }
+                                    Some(Err(err)) => {
+                                        tracing::warn!("websocket error on next peer, reconnecting: {err}");
+                                        break;
+                                    }
+                                    None => {
+                                        tracing::warn!("next peer closed connection, reconnecting");
+                                        break;
+                                    }
+                                }
                             }
-                            Some(Err(err)) => {
-                                let _ = next_recv_tx.send(Err(eyre::eyre!("websocket error: {err}"))).await;
-                                break;
+                        }
+                    }
+
+                    state.store(ConnectionState::Reconnecting as u8, Ordering::SeqCst);
+                    let next_peer_url = next_peer_url.clone();
+                    match reconnect_with_backoff(&reconnect_config, || {
+                        ws_connect(&next_peer_url, session_id)
+                    })
+                    .await
+                    {
+                        Ok(new_websocket) => {
+                            let (new_sender, new_receiver) = new_websocket.split();
+                            sender = new_sender;
+                            receiver = new_receiver;
+                            let frames: Vec<_> = resend_buffer
+                                .lock()
+                                .expect("not poisoned")
+                                .iter()
+                                .map(|(_, frame)| frame.clone())
+                                .collect();
+                            for frame in frames {
+                                if sender
+                                    .send(tokio_tungstenite::tungstenite::Message::Binary(frame.into()))
+                                    .await
+                                    .is_err()
+                                {
+                                    continue 'outer;
+                                }
                             }
-                            None => break,
+                        }
+                        Err(err) => {
+                            tracing::error!("giving up reconnecting to next peer: {err:?}");
+                            state.store(ConnectionState::Dead as u8, Ordering::SeqCst);
+                            let err_report = if timed_out {
+                                eyre::Report::new(PeerTimeout(idle_timeout))
+                            } else {
+                                eyre::eyre!("next peer unreachable: {err}")
+                            };
+                            let _ = next_recv_tx.send(Err(err_report)).await;
+                            return;
                         }
                     }
                 }
-            }
-        });
+            });
+        }
 
         let (prev_send_tx, mut prev_send_rx) = mpsc::channel::<Vec<u8>>(32);
         let (prev_recv_tx, prev_recv_rx) = mpsc::channel::<eyre::Result<Vec<u8>>>(32);
-        tokio::task::spawn(async move {
-            while let Some(data) = prev_send_rx.recv().await {
-                if let Err(err) = prev_sender
-                    .send(axum::extract::ws::Message::Binary(data.into()))
-                    .await
-                {
-                    tracing::warn!("failed to send data: {err:?}");
-                    break;
-                }
-            }
-        });
-        let cancellation_token_clone = cancellation_token.clone();
-        tokio::task::spawn(async move {
-            loop {
-                tokio::select! {
-                    _ = cancellation_token_clone.cancelled() => {
-                        break;
-                    }
-                    msg = prev_receiver.next() => {
-                        match msg {
-                            Some(Ok(axum::extract::ws::Message::Binary(data))) => {
-                                if prev_recv_tx.send(Ok(data.into())).await.is_err() {
-                                    tracing::warn!("recv receiver dropped");
+        let prev_state = Arc::new(AtomicU8::new(ConnectionState::Connected as u8));
+        {
+            let cancellation_token = cancellation_token.clone();
+            let state = prev_state.clone();
+            let reconnect_config = reconnect_config.clone();
+            tokio::task::spawn(async move {
+                let (mut sender, mut receiver) = prev_websocket.split();
+                let seq_counter = AtomicU64::new(0);
+                let next_expected = AtomicU64::new(0);
+                let resend_buffer: Mutex<VecDeque<(u64, Vec<u8>)>> = Mutex::new(VecDeque::new());
+                'outer: loop {
+                    state.store(ConnectionState::Connected as u8, Ordering::SeqCst);
+                    let mut ping_ticker = tokio::time::interval(ping_interval);
+                    ping_ticker.reset();
+                    let mut timed_out = false;
+                    loop {
+                        tokio::select! {
+                            _ = cancellation_token.cancelled() => {
+                                return;
+                            }
+                            _ = ping_ticker.tick() => {
+                                if sender
+                                    .send(axum::extract::ws::Message::Ping(Vec::new().into()))
+                                    .await
+                                    .is_err()
+                                {
+                                    tracing::warn!("failed to send ping to prev peer, reconnecting");
                                     break;
                                 }
                             }
-                            Some(Ok(_)) => {
-                                tracing::warn!("unexpected ws message: {msg:?}");
-                                let _ = prev_recv_tx.send(Err(eyre::eyre!("invalid ws message"))).await;
-                                break;
+                            maybe_data = prev_send_rx.recv() => {
+                                let Some(data) = maybe_data else { return };
+                                let seq = seq_counter.fetch_add(1, Ordering::SeqCst);
+                                let ack = next_expected.load(Ordering::SeqCst);
+                                let frame = encode_frame(seq, ack, &data);
+                                {
+                                    let mut buf = resend_buffer.lock().expect("not poisoned");
+                                    buf.push_back((seq, frame.clone()));
+                                    while buf.len() > RESEND_BUFFER_CAP {
+                                        buf.pop_front();
+                                    }
+                                }
+                                if let Err(err) = sender
+                                    .send(axum::extract::ws::Message::Binary(frame.into()))
+                                    .await
+                                {
+                                    tracing::warn!("failed to send data to prev peer, reconnecting: {err:?}");
+                                    break;
+                                }
                             }
-                            Some(Err(err)) => {
-                                let _ = prev_recv_tx.send(Err(eyre::eyre!("websocket error: {err}"))).await;
-                                break;
+                            msg = tokio::time::timeout(idle_timeout, receiver.next()) => {
+                                let Ok(msg) = msg else {
+                                    tracing::warn!("idle timeout waiting for prev peer, reconnecting");
+                                    timed_out = true;
+                                    break;
+                                };
+                                match msg {
+                                    Some(Ok(axum::extract::ws::Message::Binary(data))) => {
+                                        match decode_frame(data.into()) {
+                                            Ok((seq, ack, payload)) => {
+                                                // The peer has confirmed receiving everything
+                                                // before `ack` - those frames no longer need to
+                                                // survive a reconnect.
+                                                let mut buf = resend_buffer.lock().expect("not poisoned");
+                                                while buf.front().is_some_and(|(s, _)| *s < ack) {
+                                                    buf.pop_front();
+                                                }
+                                                drop(buf);
+
+                                                if seq >= next_expected.load(Ordering::SeqCst) {
+                                                    next_expected.store(seq + 1, Ordering::SeqCst);
+                                                    if prev_recv_tx.send(Ok(payload)).await.is_err() {
+                                                        tracing::warn!("recv receiver dropped");
+                                                        return;
+                                                    }
+                                                }
+                                            }
+                                            Err(err) => {
+                                                let _ = prev_recv_tx.send(Err(err)).await;
+                                            }
+                                        }
+                                    }
+                                    Some(Ok(axum::extract::ws::Message::Ping(payload))) => {
+                                        let _ = sender.send(axum::extract::ws::Message::Pong(payload)).await;
+                                    }
+                                    Some(Ok(axum::extract::ws::Message::Pong(_))) => {}
+                                    Some(Ok(axum::extract::ws::Message::Close(frame))) => {
+                                        tracing::info!(?frame, "prev peer closed websocket cleanly, reconnecting");
+                                        break;
+                                    }
+                                    Some(Ok(_)) => {
+                                        tracing::warn!("unexpected ws message: {msg:?}");
+                                        break;
+                                    }
+                                    Some(Err(err)) => {
+                                        tracing::warn!("websocket error on prev peer, reconnecting: {err}");
+                                        break;
+                                    }
+                                    None => {
+                                        tracing::warn!("prev peer closed connection, reconnecting");
+                                        break;
+                                    }
+                                }
                             }
-                            None => break,
+                        }
+                    }
+
+                    state.store(ConnectionState::Reconnecting as u8, Ordering::SeqCst);
+                    let ws_sessions = ws_sessions.clone();
+                    match reconnect_with_backoff(&reconnect_config, || ws_sessions.get(session_id)).await {
+                        Ok(new_websocket) => {
+                            let (new_sender, new_receiver) = new_websocket.split();
+                            sender = new_sender;
+                            receiver = new_receiver;
+                            let frames: Vec<_> = resend_buffer
+                                .lock()
+                                .expect("not poisoned")
+                                .iter()
+                                .map(|(_, frame)| frame.clone())
+                                .collect();
+                            for frame in frames {
+                                if sender
+                                    .send(axum::extract::ws::Message::Binary(frame.into()))
+                                    .await
+                                    .is_err()
+                                {
+                                    continue 'outer;
+                                }
+                            }
+                        }
+                        Err(err) => {
+                            tracing::error!("giving up reconnecting to prev peer: {err:?}");
+                            state.store(ConnectionState::Dead as u8, Ordering::SeqCst);
+                            let err_report = if timed_out {
+                                eyre::Report::new(PeerTimeout(idle_timeout))
+                            } else {
+                                eyre::eyre!("prev peer unreachable: {err}")
+                            };
+                            let _ = prev_recv_tx.send(Err(err_report)).await;
+                            return;
                         }
                     }
                 }
-            }
-        });
+            });
+        }
 
         send.insert(id.next().into(), (next_send_tx, AtomicUsize::default()));
         send.insert(id.prev().into(), (prev_send_tx, AtomicUsize::default()));
@@ -249,8 +722,94 @@ impl WebSocketNetwork {
             id.prev().into(),
             (Mutex::new(prev_recv_rx), AtomicUsize::default()),
         );
+        state.insert(id.next().into(), next_state);
+        state.insert(id.prev().into(), prev_state);
+
+        Ok(Self {
+            id,
+            send,
+            recv,
+            state,
+        })
+    }
+
+    /// Builds an MPC [`Network`] over two sessions already opened on a shared, multiplexed
+    /// connection (see [`crate::mux`]) instead of owning a raw per-link socket like [`Self::new`].
+    ///
+    /// Unlike [`Self::new`], a dropped handle here does not redial - see the [`crate::mux`] module
+    /// docs for why - so frames aren't tagged with a sequence number/ack either, since that
+    /// bookkeeping in [`Self::new`] exists only to de-duplicate frames resent after a reconnect.
+    pub fn from_mux_handles(
+        id: PartyID,
+        next_handle: mux::MuxSessionHandle,
+        prev_handle: mux::MuxSessionHandle,
+        cancellation_token: CancellationToken,
+        idle_timeout: Duration,
+    ) -> eyre::Result<Self> {
+        let mut send = HashMap::new();
+        let mut recv = HashMap::new();
+        let mut state = HashMap::new();
+
+        let (next_send_tx, next_send_rx) = mpsc::channel::<Vec<u8>>(32);
+        let (next_recv_tx, next_recv_rx) = mpsc::channel::<eyre::Result<Vec<u8>>>(32);
+        let next_state = Arc::new(AtomicU8::new(ConnectionState::Connected as u8));
+        {
+            let (sender, in_rx) = next_handle.split();
+            tokio::task::spawn(run_mux_link(
+                "next",
+                sender,
+                in_rx,
+                cancellation_token.clone(),
+                idle_timeout,
+                next_state.clone(),
+                next_send_rx,
+                next_recv_tx,
+            ));
+        }
+
+        let (prev_send_tx, prev_send_rx) = mpsc::channel::<Vec<u8>>(32);
+        let (prev_recv_tx, prev_recv_rx) = mpsc::channel::<eyre::Result<Vec<u8>>>(32);
+        let prev_state = Arc::new(AtomicU8::new(ConnectionState::Connected as u8));
+        {
+            let (sender, in_rx) = prev_handle.split();
+            tokio::task::spawn(run_mux_link(
+                "prev",
+                sender,
+                in_rx,
+                cancellation_token,
+                idle_timeout,
+                prev_state.clone(),
+                prev_send_rx,
+                prev_recv_tx,
+            ));
+        }
+
+        send.insert(id.next().into(), (next_send_tx, AtomicUsize::default()));
+        send.insert(id.prev().into(), (prev_send_tx, AtomicUsize::default()));
+        recv.insert(
+            id.next().into(),
+            (Mutex::new(next_recv_rx), AtomicUsize::default()),
+        );
+        recv.insert(
+            id.prev().into(),
+            (Mutex::new(prev_recv_rx), AtomicUsize::default()),
+        );
+        state.insert(id.next().into(), next_state);
+        state.insert(id.prev().into(), prev_state);
+
+        Ok(Self {
+            id,
+            send,
+            recv,
+            state,
+        })
+    }
 
-        Ok(Self { id, send, recv })
+    /// Current reconnection state of the link to `party`, i.e. whether frames are flowing,
+    /// a redial is in progress, or the link has given up for good.
+    pub fn connection_state(&self, party: usize) -> eyre::Result<ConnectionState> {
+        let state = self.state.get(&party).context("party id out-of-bounds")?;
+        Ok(ConnectionState::from_u8(state.load(Ordering::Relaxed)))
     }
 }
 
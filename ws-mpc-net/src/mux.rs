@@ -0,0 +1,338 @@
+//! Multiplexes many logical MPC sessions over one persistent per-peer websocket, instead of
+//! paying a full handshake per [`crate::WebSocketNetwork`] session.
+//!
+//! Every frame is prefixed with a 16-byte session [`Uuid`] and a one-byte control tag
+//! ([`MuxControl`]); [`SessionRouter`] demultiplexes inbound frames by that id into per-session
+//! `mpsc` channels, creating the channel lazily if the session hasn't been accepted locally yet
+//! (mirroring how [`crate::WsSessions`] parks a `Ready` socket nobody's claimed yet) and
+//! reclaiming it once a [`MuxControl::Close`] frame arrives.
+//!
+//! [`WebSocketNetwork::from_mux_handles`](crate::WebSocketNetwork::from_mux_handles) consumes the
+//! [`MuxSessionHandle`]s this module produces, so `WebSocketNetwork` no longer has to own a raw
+//! per-session socket. It does not redial on disconnect the way
+//! [`WebSocketNetwork::new`](crate::WebSocketNetwork::new) does, though: redialing a multiplexed
+//! session means re-establishing the whole shared [`MuxConnection`] and re-opening every session
+//! multiplexed over it, not just this one - that's a connection-level concern for whichever caller
+//! owns the `MuxConnection`, and isn't built yet, so a dropped handle just marks its link dead
+//! instead of resuming it. No caller drives that redial yet, so nothing today routes its MPC
+//! traffic through a shared `MuxConnection` in production - adopting one for a given transport is
+//! left to whichever follow-up wants the handshake savings badly enough to also own that redial.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+use futures::{SinkExt as _, StreamExt as _};
+use tokio::sync::{Mutex, mpsc};
+use uuid::Uuid;
+
+use crate::{ClientWsStream, ServerWsStream};
+
+const MUX_HEADER_LEN: usize = 17;
+
+/// Tag byte following a mux frame's session id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MuxControl {
+    /// Announces a new logical session; carries no payload of its own.
+    Open = 0,
+    /// Carries a payload for an already-open (or about-to-be-accepted) session.
+    Data = 1,
+    /// Tells the peer's router to reclaim this session's channel state.
+    Close = 2,
+}
+
+impl MuxControl {
+    fn from_u8(value: u8) -> eyre::Result<Self> {
+        match value {
+            0 => Ok(Self::Open),
+            1 => Ok(Self::Data),
+            2 => Ok(Self::Close),
+            other => eyre::bail!("unknown mux control byte {other}"),
+        }
+    }
+}
+
+/// Prefixes `payload` with `session_id` and `control` so one persistent websocket can carry
+/// many logical sessions - see the module docs.
+fn encode_mux_frame(session_id: Uuid, control: MuxControl, payload: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(MUX_HEADER_LEN + payload.len());
+    frame.extend_from_slice(session_id.as_bytes());
+    frame.push(control as u8);
+    frame.extend_from_slice(payload);
+    frame
+}
+
+/// Splits a frame produced by [`encode_mux_frame`] back into its session id, control tag, and
+/// payload.
+fn decode_mux_frame(data: Vec<u8>) -> eyre::Result<(Uuid, MuxControl, Vec<u8>)> {
+    eyre::ensure!(
+        data.len() >= MUX_HEADER_LEN,
+        "mux frame too short to contain a session id and control byte"
+    );
+    let (header, payload) = data.split_at(MUX_HEADER_LEN);
+    let session_id = Uuid::from_bytes(header[..16].try_into().expect("checked length above"));
+    let control = MuxControl::from_u8(header[16])?;
+    Ok((session_id, control, payload.to_vec()))
+}
+
+/// A session's demultiplexing state: either nobody has called [`SessionRouter::accept_session`]
+/// for it yet and inbound payloads are buffered until they do, or they have and payloads are
+/// forwarded straight through.
+enum MuxSlot {
+    Ready(VecDeque<Vec<u8>>),
+    Waiting(mpsc::Sender<Vec<u8>>),
+}
+
+/// Demultiplexes inbound mux frames by session id for one [`MuxConnection`].
+#[derive(Clone, Default)]
+struct SessionRouter {
+    slots: Arc<Mutex<HashMap<Uuid, MuxSlot>>>,
+}
+
+impl SessionRouter {
+    async fn route(&self, session_id: Uuid, payload: Vec<u8>) {
+        let mut slots = self.slots.lock().await;
+        match slots.get_mut(&session_id) {
+            Some(MuxSlot::Waiting(tx)) => {
+                let tx = tx.clone();
+                drop(slots);
+                let _ = tx.send(payload).await;
+            }
+            Some(MuxSlot::Ready(buf)) => buf.push_back(payload),
+            None => {
+                slots.insert(session_id, MuxSlot::Ready(VecDeque::from([payload])));
+            }
+        }
+    }
+
+    async fn remove_session(&self, session_id: Uuid) {
+        self.slots.lock().await.remove(&session_id);
+    }
+
+    /// Claims `session_id`'s inbound frames, replaying anything buffered before this call.
+    async fn accept_session(&self, session_id: Uuid) -> mpsc::Receiver<Vec<u8>> {
+        let (tx, rx) = mpsc::channel(32);
+        let mut slots = self.slots.lock().await;
+        match slots.remove(&session_id) {
+            Some(MuxSlot::Ready(buffered)) => {
+                slots.insert(session_id, MuxSlot::Waiting(tx.clone()));
+                drop(slots);
+                for payload in buffered {
+                    let _ = tx.send(payload).await;
+                }
+            }
+            Some(MuxSlot::Waiting(_)) | None => {
+                slots.insert(session_id, MuxSlot::Waiting(tx));
+            }
+        }
+        rx
+    }
+}
+
+/// One logical session multiplexed over a [`MuxConnection`]: sends are tagged with `session_id`
+/// and pushed onto the connection's shared outbound queue; receives come off the per-session
+/// channel the connection's router demultiplexes into.
+pub struct MuxSessionHandle {
+    sender: MuxSessionSender,
+    in_rx: mpsc::Receiver<Vec<u8>>,
+}
+
+impl MuxSessionHandle {
+    pub async fn send(&self, payload: Vec<u8>) -> eyre::Result<()> {
+        self.sender.send(payload).await
+    }
+
+    pub async fn recv(&mut self) -> Option<Vec<u8>> {
+        self.in_rx.recv().await
+    }
+
+    /// Tells the peer's router to reclaim this session's channel state. Does not affect other
+    /// sessions multiplexed over the same connection.
+    pub async fn close(&self) {
+        self.sender.close().await
+    }
+
+    /// Splits into independent send/receive halves so both can be driven concurrently (e.g. from
+    /// the two branches of a `tokio::select!`) without a conflicting borrow of one handle - `send`
+    /// only needs `&self` but `recv` needs `&mut self`, and both would otherwise have to be live
+    /// at once.
+    pub fn split(self) -> (MuxSessionSender, mpsc::Receiver<Vec<u8>>) {
+        (self.sender, self.in_rx)
+    }
+}
+
+/// The send half of a [`MuxSessionHandle`], split off by [`MuxSessionHandle::split`].
+#[derive(Clone)]
+pub struct MuxSessionSender {
+    session_id: Uuid,
+    out_tx: mpsc::Sender<Vec<u8>>,
+}
+
+impl MuxSessionSender {
+    pub async fn send(&self, payload: Vec<u8>) -> eyre::Result<()> {
+        self.out_tx
+            .send(encode_mux_frame(self.session_id, MuxControl::Data, &payload))
+            .await
+            .map_err(|_| eyre::eyre!("mux connection closed"))
+    }
+
+    /// Tells the peer's router to reclaim this session's channel state. Does not affect other
+    /// sessions multiplexed over the same connection.
+    pub async fn close(&self) {
+        let _ = self
+            .out_tx
+            .send(encode_mux_frame(self.session_id, MuxControl::Close, &[]))
+            .await;
+    }
+}
+
+/// One persistent, multiplexed websocket shared by every [`MuxSessionHandle`] opened over it.
+#[derive(Clone)]
+pub struct MuxConnection {
+    router: SessionRouter,
+    out_tx: mpsc::Sender<Vec<u8>>,
+}
+
+impl MuxConnection {
+    /// Wraps a client-dialed websocket, spawning the task that serializes outbound frames and
+    /// demultiplexes inbound ones via `router`.
+    pub fn from_client(stream: ClientWsStream) -> Self {
+        let router = SessionRouter::default();
+        let (out_tx, out_rx) = mpsc::channel(128);
+        tokio::spawn(run_client_mux(stream, router.clone(), out_rx));
+        Self { router, out_tx }
+    }
+
+    /// Wraps a server-accepted websocket, spawning the task that serializes outbound frames and
+    /// demultiplexes inbound ones via `router`.
+    pub fn from_server(stream: ServerWsStream) -> Self {
+        let router = SessionRouter::default();
+        let (out_tx, out_rx) = mpsc::channel(128);
+        tokio::spawn(run_server_mux(stream, router.clone(), out_rx));
+        Self { router, out_tx }
+    }
+
+    /// Opens (or accepts) `session_id`, announcing it to the peer and returning a handle that
+    /// sends/receives payloads demultiplexed for just that session.
+    pub async fn open_session(&self, session_id: Uuid) -> MuxSessionHandle {
+        let in_rx = self.router.accept_session(session_id).await;
+        let _ = self
+            .out_tx
+            .send(encode_mux_frame(session_id, MuxControl::Open, &[]))
+            .await;
+        MuxSessionHandle {
+            sender: MuxSessionSender {
+                session_id,
+                out_tx: self.out_tx.clone(),
+            },
+            in_rx,
+        }
+    }
+}
+
+async fn run_client_mux(
+    stream: ClientWsStream,
+    router: SessionRouter,
+    mut out_rx: mpsc::Receiver<Vec<u8>>,
+) {
+    let (mut sender, mut receiver) = stream.split();
+    loop {
+        tokio::select! {
+            maybe_frame = out_rx.recv() => {
+                let Some(frame) = maybe_frame else { return };
+                if let Err(err) = sender
+                    .send(tokio_tungstenite::tungstenite::Message::Binary(frame.into()))
+                    .await
+                {
+                    tracing::warn!("failed to send mux frame: {err:?}");
+                    return;
+                }
+            }
+            msg = receiver.next() => {
+                match msg {
+                    Some(Ok(tokio_tungstenite::tungstenite::Message::Binary(data))) => {
+                        match decode_mux_frame(data.into()) {
+                            Ok((session_id, MuxControl::Close, _)) => {
+                                router.remove_session(session_id).await;
+                            }
+                            Ok((_, MuxControl::Open, _)) => {}
+                            Ok((session_id, _, payload)) => router.route(session_id, payload).await,
+                            Err(err) => tracing::warn!("invalid mux frame: {err:?}"),
+                        }
+                    }
+                    Some(Ok(tokio_tungstenite::tungstenite::Message::Ping(payload))) => {
+                        let _ = sender
+                            .send(tokio_tungstenite::tungstenite::Message::Pong(payload))
+                            .await;
+                    }
+                    Some(Ok(tokio_tungstenite::tungstenite::Message::Pong(_))) => {}
+                    Some(Ok(tokio_tungstenite::tungstenite::Message::Close(frame))) => {
+                        tracing::info!(?frame, "mux connection closed cleanly");
+                        return;
+                    }
+                    Some(Ok(_)) => {}
+                    Some(Err(err)) => {
+                        tracing::warn!("mux connection error: {err}");
+                        return;
+                    }
+                    None => {
+                        tracing::warn!("mux connection closed");
+                        return;
+                    }
+                }
+            }
+        }
+    }
+}
+
+async fn run_server_mux(
+    stream: ServerWsStream,
+    router: SessionRouter,
+    mut out_rx: mpsc::Receiver<Vec<u8>>,
+) {
+    let (mut sender, mut receiver) = stream.split();
+    loop {
+        tokio::select! {
+            maybe_frame = out_rx.recv() => {
+                let Some(frame) = maybe_frame else { return };
+                if let Err(err) = sender
+                    .send(axum::extract::ws::Message::Binary(frame.into()))
+                    .await
+                {
+                    tracing::warn!("failed to send mux frame: {err:?}");
+                    return;
+                }
+            }
+            msg = receiver.next() => {
+                match msg {
+                    Some(Ok(axum::extract::ws::Message::Binary(data))) => {
+                        match decode_mux_frame(data.into()) {
+                            Ok((session_id, MuxControl::Close, _)) => {
+                                router.remove_session(session_id).await;
+                            }
+                            Ok((_, MuxControl::Open, _)) => {}
+                            Ok((session_id, _, payload)) => router.route(session_id, payload).await,
+                            Err(err) => tracing::warn!("invalid mux frame: {err:?}"),
+                        }
+                    }
+                    Some(Ok(axum::extract::ws::Message::Ping(payload))) => {
+                        let _ = sender.send(axum::extract::ws::Message::Pong(payload)).await;
+                    }
+                    Some(Ok(axum::extract::ws::Message::Pong(_))) => {}
+                    Some(Ok(axum::extract::ws::Message::Close(frame))) => {
+                        tracing::info!(?frame, "mux connection closed cleanly");
+                        return;
+                    }
+                    Some(Ok(_)) => {}
+                    Some(Err(err)) => {
+                        tracing::warn!("mux connection error: {err}");
+                        return;
+                    }
+                    None => {
+                        tracing::warn!("mux connection closed");
+                        return;
+                    }
+                }
+            }
+        }
+    }
+}